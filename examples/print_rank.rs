@@ -17,7 +17,7 @@ async fn main() {
         .seasons
         .get(&Season::ShadowLegacy)
         .and_then(|s| s.regions.get(&Region::Emea))
-        .and_then(|r| r.get(0))
+        .and_then(|r| r.first())
         .map(|r| r.rank_text.clone())
         .expect("Couldn't get rank of current season");
 
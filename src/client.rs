@@ -1,11 +1,21 @@
 //! Module containing the client.
 
+use crate::http::error::url_error;
+use crate::http::{CacheBuilder, CacheStats, RetryPolicyBuilder, DEFAULT_BASE_URL};
 #[cfg(feature = "ratelimiting")]
-use crate::http::{Ratelimit, RatelimitBuilder};
+use crate::http::{Ratelimit, RatelimitBackend, RatelimitBuilder, SharedRatelimit};
 use crate::internals::Rc;
 use crate::leaderboard::Client as LeaderboardClient;
-use crate::stats::Client as StatsClient;
+use crate::search::Client as SearchClient;
+use crate::stats::{Client as StatsClient, Resolver};
 use crate::{Error, Http};
+use reqwest::{Client as ReqwestClient, ClientBuilder as ReqwestClientBuilder, Proxy, Url};
+#[cfg(feature = "ratelimiting")]
+use std::sync::Arc;
+use std::time::Duration;
+use url::ParseError;
+
+const DEFAULT_USER_AGENT: &str = concat!("r6stats_client/", env!("CARGO_PKG_VERSION"));
 
 /// Client for the r6stats API.
 ///
@@ -22,60 +32,47 @@ pub struct Client {
     http: Rc<Http>,
     stats: StatsClient,
     leaderboard: LeaderboardClient,
+    search: SearchClient,
 }
 
 impl Client {
-    fn _new(
-        token: &str,
-        #[cfg(feature = "ratelimiting")] ratelimit: Ratelimit,
-    ) -> Result<Self, Error> {
-        let http = Rc::new(Http::new(
-            token,
-            #[cfg(feature = "ratelimiting")]
-            ratelimit,
-        )?);
+    fn _new(http: Http, resolver: Resolver) -> Self {
+        let http = Rc::new(http);
+        // Shared, so the players of every endpoint can be resolved
+        let resolver = Rc::new(resolver);
 
-        let stats = StatsClient::new(http.clone());
-        let leaderboard = LeaderboardClient::new(http.clone());
+        let stats = StatsClient::new(http.clone(), resolver.clone());
+        let leaderboard = LeaderboardClient::new(http.clone(), resolver.clone());
+        let search = SearchClient::new(http.clone(), resolver);
 
-        let s = Self {
+        Self {
             stats,
             leaderboard,
+            search,
             http,
-        };
-        Ok(s)
+        }
     }
 
-    /// Creates a new client with default ratelimiting.
+    /// Creates a new client with the default settings.
+    ///
+    /// Shorthand for `Client::builder(token).build()`.
     ///
     /// # Args
     ///
     /// - `token` - The API key for authentication with the endpoint
     pub fn new(token: impl AsRef<str>) -> Result<Self, Error> {
-        Self::_new(
-            token.as_ref(),
-            #[cfg(feature = "ratelimiting")]
-            Ratelimit::default(),
-        )
+        ClientBuilder::new(token).build()
     }
 
-    /// Creates a new client with custom ratelimit settings.
+    /// Returns a [`ClientBuilder`] for configuring a client.
     ///
     /// # Args
     ///
     /// - `token` - The API key for authentication with the endpoint
-    /// - `op` - Closure for building the [`Ratelimit`]
     ///
-    /// [`Ratelimit`]: http/ratelimit/struct.Ratelimit.html
-    #[cfg(feature = "ratelimiting")]
-    pub fn with_ratelimit<F>(token: impl AsRef<str>, op: F) -> Result<Self, Error>
-    where
-        F: FnOnce(RatelimitBuilder) -> RatelimitBuilder,
-    {
-        let builder = op(RatelimitBuilder::new());
-        let ratelimit = builder.build();
-
-        Self::_new(token.as_ref(), ratelimit)
+    /// [`ClientBuilder`]: struct.ClientBuilder.html
+    pub fn builder(token: impl AsRef<str>) -> ClientBuilder {
+        ClientBuilder::new(token)
     }
 
     /// Returns the client for requests to the stats endpoint.
@@ -88,9 +85,465 @@ impl Client {
         &self.leaderboard
     }
 
+    /// Returns the client for requests to the player search endpoint.
+    pub fn search(&self) -> &SearchClient {
+        &self.search
+    }
+
+    /// Returns the base URL all requests are sent to.
+    pub fn base_url(&self) -> &Url {
+        self.http.base_url()
+    }
+
+    /// Returns the counters of the response cache, if enabled.
+    pub async fn cache_stats(&self) -> Option<CacheStats> {
+        self.http.cache_stats().await
+    }
+
     /// Returns the current ratelimit.
+    ///
+    /// Fails if the [`RatelimitBackend`] can't be read.
+    ///
+    /// [`RatelimitBackend`]: http/trait.RatelimitBackend.html
     #[cfg(feature = "ratelimiting")]
-    pub async fn ratelimit(&self) -> Ratelimit {
+    pub async fn ratelimit(&self) -> Result<Ratelimit, Error> {
         self.http.ratelimit().await
     }
 }
+
+/// The builder for [`Client`].
+///
+/// ## Example
+/// ```rust
+/// use r6stats_client::Client;
+/// use std::time::Duration;
+///
+/// let client = Client::builder("<API KEY HERE>")
+///     .timeout(Duration::from_secs(10))
+///     .connect_timeout(Duration::from_secs(3))
+///     .user_agent("my-bot/1.0")
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// [`Client`]: struct.Client.html
+#[derive(Clone, Debug)]
+pub struct ClientBuilder {
+    token: String,
+    base_url: Option<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxies: Vec<Proxy>,
+    no_proxy: bool,
+    pool_idle_timeout: Option<Option<Duration>>,
+    pool_max_idle_per_host: Option<usize>,
+    http_client: Option<ReqwestClient>,
+    retry: RetryPolicyBuilder,
+    cache: Option<CacheBuilder>,
+    resolver_capacity: Option<usize>,
+    #[cfg(feature = "ratelimiting")]
+    ratelimit: RatelimitBuilder,
+    #[cfg(feature = "ratelimiting")]
+    ratelimit_backend: Option<Arc<dyn RatelimitBackend>>,
+}
+
+impl ClientBuilder {
+    /// Returns a new builder.
+    ///
+    /// # Args
+    ///
+    /// - `token` - The API key for authentication with the endpoint
+    pub fn new(token: impl AsRef<str>) -> Self {
+        Self {
+            token: token.as_ref().to_string(),
+            base_url: None,
+            timeout: None,
+            connect_timeout: None,
+            user_agent: None,
+            proxies: Vec::new(),
+            no_proxy: false,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            http_client: None,
+            retry: RetryPolicyBuilder::new(),
+            cache: None,
+            resolver_capacity: None,
+            #[cfg(feature = "ratelimiting")]
+            ratelimit: RatelimitBuilder::new(),
+            #[cfg(feature = "ratelimiting")]
+            ratelimit_backend: None,
+        }
+    }
+
+    /// Sets the base URL the routes are joined onto.
+    ///
+    /// Defaults to `https://api2.r6stats.com/public-api/`. Useful for running against a local
+    /// mock server.
+    pub fn base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        self.base_url = Some(base_url.as_ref().to_string());
+        self
+    }
+
+    /// Sets a timeout for the whole request, from connecting until the response body is read.
+    ///
+    /// Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a timeout for only the connect phase.
+    ///
+    /// Default is no timeout.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request.
+    ///
+    /// Defaults to `r6stats_client/<version>`.
+    pub fn user_agent(mut self, user_agent: impl AsRef<str>) -> Self {
+        self.user_agent = Some(user_agent.as_ref().to_string());
+        self
+    }
+
+    /// Adds a [`Proxy`] the requests are sent through.
+    ///
+    /// Can be called multiple times, the first matching proxy is used.
+    ///
+    /// [`Proxy`]: https://docs.rs/reqwest/0.10/reqwest/struct.Proxy.html
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Disables all proxies, including the system proxies picked up from the environment.
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Sets the time an idle connection is kept alive in the pool.
+    ///
+    /// Pass `None` to keep idle connections forever. Defaults to 90 seconds.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept per host.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Uses a preconfigured [`reqwest::Client`] for sending the requests.
+    ///
+    /// The client is used as is, so the timeout, user agent, proxy and pool settings of this
+    /// builder are ignored.
+    ///
+    /// [`reqwest::Client`]: https://docs.rs/reqwest/0.10/reqwest/struct.Client.html
+    pub fn http_client(mut self, client: ReqwestClient) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Configures the [`RetryPolicy`] of the client.
+    ///
+    /// By default, failed requests are not retried.
+    ///
+    /// # Args
+    ///
+    /// - `op` - Closure for building the [`RetryPolicy`]
+    ///
+    /// [`RetryPolicy`]: http/struct.RetryPolicy.html
+    pub fn retry<F>(mut self, op: F) -> Self
+    where
+        F: FnOnce(RetryPolicyBuilder) -> RetryPolicyBuilder,
+    {
+        self.retry = op(self.retry);
+        self
+    }
+
+    /// Enables and configures the response cache.
+    ///
+    /// Cached responses are served without sending a request until they expire. The cache is
+    /// disabled by default.
+    ///
+    /// # Args
+    ///
+    /// - `op` - Closure for building the cache
+    pub fn cache<F>(mut self, op: F) -> Self
+    where
+        F: FnOnce(CacheBuilder) -> CacheBuilder,
+    {
+        self.cache = Some(op(self.cache.unwrap_or_default()));
+        self
+    }
+
+    /// Sets the number of Ubisoft IDs the [`Resolver`] of the client knows.
+    ///
+    /// Defaults to 10 000.
+    ///
+    /// [`Resolver`]: stats/struct.Resolver.html
+    pub fn resolver_capacity(mut self, capacity: usize) -> Self {
+        self.resolver_capacity = Some(capacity);
+        self
+    }
+
+    /// Configures the [`Ratelimit`] of the client.
+    ///
+    /// # Args
+    ///
+    /// - `op` - Closure for building the [`Ratelimit`]
+    ///
+    /// [`Ratelimit`]: http/ratelimit/struct.Ratelimit.html
+    #[cfg(feature = "ratelimiting")]
+    pub fn ratelimit<F>(mut self, op: F) -> Self
+    where
+        F: FnOnce(RatelimitBuilder) -> RatelimitBuilder,
+    {
+        self.ratelimit = op(self.ratelimit);
+        self
+    }
+
+    /// Sets the [`RatelimitBackend`] keeping track of the ratelimit, e.g. for sharing it with
+    /// other clients.
+    ///
+    /// Overrides the settings of [`ratelimit`].
+    ///
+    /// [`RatelimitBackend`]: http/trait.RatelimitBackend.html
+    /// [`ratelimit`]: #method.ratelimit
+    #[cfg(feature = "ratelimiting")]
+    pub fn ratelimit_backend(mut self, backend: impl RatelimitBackend + 'static) -> Self {
+        self.ratelimit_backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// Returns the finished [`Client`].
+    ///
+    /// Fails if the base URL is malformed or the underlying HTTP client can't be built.
+    ///
+    /// [`Client`]: struct.Client.html
+    pub fn build(mut self) -> Result<Client, Error> {
+        let base_url = self.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        let base_url = parse_base_url(base_url)?;
+
+        let client = match self.http_client.take() {
+            Some(client) => client,
+            None => self.build_http_client()?,
+        };
+
+        #[cfg(feature = "ratelimiting")]
+        let ratelimit: Arc<dyn RatelimitBackend> = match self.ratelimit_backend.take() {
+            Some(backend) => backend,
+            None => Arc::new(SharedRatelimit::from_ratelimit(self.ratelimit.build())),
+        };
+
+        let http = Http::new(
+            &self.token,
+            base_url,
+            client,
+            self.retry.build(),
+            self.cache.map(CacheBuilder::build),
+            #[cfg(feature = "ratelimiting")]
+            ratelimit,
+        );
+
+        let resolver = self
+            .resolver_capacity
+            .map_or_else(Resolver::default, Resolver::new);
+
+        Ok(Client::_new(http, resolver))
+    }
+
+    fn build_http_client(&self) -> Result<ReqwestClient, Error> {
+        let user_agent = self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        let mut builder = ReqwestClientBuilder::new()
+            .use_rustls_tls()
+            .user_agent(user_agent);
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+
+        for proxy in self.proxies.iter().cloned() {
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+fn parse_base_url(base_url: &str) -> Result<Url, Error> {
+    let url = Url::parse(base_url).map_err(|e| url_error(base_url, e))?;
+
+    if url.cannot_be_a_base() {
+        return Err(url_error(
+            base_url,
+            ParseError::RelativeUrlWithCannotBeABaseBase,
+        ));
+    }
+
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientBuilder, DEFAULT_USER_AGENT};
+    use crate::test_util::{MockResponse, MockServer};
+    use crate::{Error, Platform};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::{Client as ReqwestClient, Proxy};
+    use std::time::Duration;
+
+    const LEADERBOARD_PATH: &str = "/leaderboard/pc/all";
+
+    #[test]
+    fn test_default_base_url() {
+        let client = ClientBuilder::new("").build().unwrap();
+
+        assert_eq!(
+            client.base_url().as_str(),
+            "https://api2.r6stats.com/public-api/"
+        );
+    }
+
+    #[test]
+    fn test_custom_base_url() {
+        let client = ClientBuilder::new("")
+            .base_url("http://127.0.0.1:8080/mock")
+            .build()
+            .unwrap();
+
+        assert_eq!(client.base_url().as_str(), "http://127.0.0.1:8080/mock");
+    }
+
+    #[test]
+    fn test_clone_builder() {
+        let builder = ClientBuilder::new("").base_url("http://127.0.0.1:8080/mock");
+
+        let first = builder.clone().build().unwrap();
+        let second = builder.build().unwrap();
+
+        assert_eq!(first.base_url(), second.base_url());
+    }
+
+    #[test]
+    fn test_invalid_base_url() {
+        let bases = ["", "/relative", "mailto:someone@example.com"];
+
+        for &base in bases.iter() {
+            assert!(ClientBuilder::new("").base_url(base).build().is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_agent() {
+        let server = MockServer::start().await;
+
+        let client = server.client_builder("").build().unwrap();
+        let _ = client.leaderboard().get(Platform::Pc, None).await;
+
+        let client = server
+            .client_builder("")
+            .user_agent("my-bot/1.0")
+            .build()
+            .unwrap();
+        let _ = client.leaderboard().get(Platform::Pc, None).await;
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("user-agent"), Some(DEFAULT_USER_AGENT));
+        assert_eq!(requests[1].header("user-agent"), Some("my-bot/1.0"));
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let server = MockServer::start().await;
+        server.mount(
+            LEADERBOARD_PATH,
+            MockResponse::json("[]").delay(Duration::from_millis(500)),
+        );
+
+        let client = server
+            .client_builder("")
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        let err = client
+            .leaderboard()
+            .get(Platform::Pc, None)
+            .await
+            .unwrap_err();
+
+        match err {
+            Error::HttpError(err) => assert!(err.request_error().unwrap().is_timeout()),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_proxy() {
+        let server = MockServer::start().await;
+        server.mount(LEADERBOARD_PATH, MockResponse::json("[]"));
+
+        // The unresolvable host is only reachable through the proxy
+        let client = ClientBuilder::new("")
+            .base_url("http://r6stats.invalid/")
+            .proxy(Proxy::http(&server.base_url()).unwrap())
+            .build()
+            .unwrap();
+
+        client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].header("host"), Some("r6stats.invalid"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client() {
+        let server = MockServer::start().await;
+        server.mount(LEADERBOARD_PATH, MockResponse::json("[]"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-custom", HeaderValue::from_static("custom"));
+        let http_client = ReqwestClient::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let client = server
+            .client_builder("TOKEN")
+            .user_agent("ignored")
+            .http_client(http_client)
+            .build()
+            .unwrap();
+
+        client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("x-custom"), Some("custom"));
+        assert_eq!(requests[0].header("user-agent"), None);
+        assert_eq!(requests[0].token.as_deref(), Some("TOKEN"));
+    }
+}
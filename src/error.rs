@@ -1,20 +1,128 @@
 //! Module containing crate errors.
 
 use crate::http::error::{request_error, Error as HttpError};
-use reqwest::Error as ReqwestError;
+use crate::Platform;
+use reqwest::{Error as ReqwestError, StatusCode};
 use serde_json::Error as JsonError;
 use std::error::Error as StdError;
 use std::fmt::{self, Display, Formatter};
+use std::io::Error as IoError;
+use std::time::Duration;
+
 /// Enum containing all errors.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum Error {
+    /// The API doesn't know the player on the platform.
+    PlayerNotFound {
+        /// The requested username.
+        username: String,
+        /// The requested platform.
+        platform: Platform,
+        /// The error message sent by the API, like `"Player has no stats"`.
+        message: Option<String>,
+        /// The unsuccessful response, with its URL and body.
+        source: Box<HttpError>,
+    },
+    /// The API rejected the token.
+    Unauthorized {
+        /// The error message sent by the API.
+        message: Option<String>,
+        /// The unsuccessful response, with its URL and body.
+        source: Box<HttpError>,
+    },
+    /// The API ratelimit was exceeded.
+    RateLimited {
+        /// The delay requested by the server's `Retry-After` header, if sent.
+        retry_after: Option<Duration>,
+        /// The error message sent by the API.
+        message: Option<String>,
+        /// The unsuccessful response, with its URL and body.
+        source: Box<HttpError>,
+    },
+    /// The API failed to handle the request.
+    ServerError {
+        /// The status code of the response.
+        status: StatusCode,
+        /// The error message sent by the API.
+        message: Option<String>,
+        /// The unsuccessful response, with its URL and body.
+        source: Box<HttpError>,
+    },
     HttpError(HttpError),
     JsonError(JsonError),
     UsernameMalformed,
+    /// The search query is blank, or a path segment like `..`.
+    QueryMalformed,
+    /// No username is known for the Ubisoft ID.
+    UnknownUbisoftId(String),
+    IoError(IoError),
     Other(String),
 }
 
+impl Error {
+    /// Returns the status code, if the error was generated from a response.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::PlayerNotFound { .. } => Some(StatusCode::NOT_FOUND),
+            Self::Unauthorized { .. } => Some(StatusCode::UNAUTHORIZED),
+            Self::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            Self::ServerError { status, .. } => Some(*status),
+            Self::HttpError(err) => err.unsuccessful_request(),
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP error, along with the URL and body of the response if there was one.
+    ///
+    /// Available for [`HttpError`]s and the errors generated from an unsuccessful response, like
+    /// [`ServerError`].
+    ///
+    /// [`HttpError`]: #variant.HttpError
+    /// [`ServerError`]: #variant.ServerError
+    pub fn http_error(&self) -> Option<&HttpError> {
+        match self {
+            Self::HttpError(err) => Some(err),
+            Self::PlayerNotFound { source, .. }
+            | Self::Unauthorized { source, .. }
+            | Self::RateLimited { source, .. }
+            | Self::ServerError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the error is transient, so sending the request again may succeed.
+    ///
+    /// That's the case for ratelimits, server errors, `408 Request Timeout` and requests which
+    /// failed on their way to the server. Which of them a client retries is up to its
+    /// [`RetryPolicy`].
+    ///
+    /// [`RetryPolicy`]: ../http/struct.RetryPolicy.html
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::ServerError { .. } => true,
+            Self::HttpError(err) => match err.request_error() {
+                Some(err) => {
+                    err.is_timeout() || err.is_connect() || err.is_request() || err.is_body()
+                }
+                None => err.unsuccessful_request() == Some(StatusCode::REQUEST_TIMEOUT),
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns true if the request was rejected because of the given arguments or token.
+    ///
+    /// Sending the same request again won't succeed.
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            Self::UsernameMalformed | Self::QueryMalformed | Self::UnknownUbisoftId(_) => true,
+            Self::RateLimited { .. } => false,
+            _ => self.status().is_some_and(|s| s.is_client_error()),
+        }
+    }
+}
+
 impl From<ReqwestError> for Error {
     fn from(e: ReqwestError) -> Self {
         request_error(e)
@@ -27,11 +135,22 @@ impl From<JsonError> for Error {
     }
 }
 
+impl From<IoError> for Error {
+    fn from(e: IoError) -> Self {
+        Self::IoError(e)
+    }
+}
+
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Self::HttpError(err) => Some(err),
+            Self::PlayerNotFound { source, .. }
+            | Self::Unauthorized { source, .. }
+            | Self::RateLimited { source, .. }
+            | Self::ServerError { source, .. } => Some(&**source),
             Self::JsonError(err) => Some(err),
+            Self::IoError(err) => Some(err),
             _ => None,
         }
     }
@@ -40,10 +159,54 @@ impl StdError for Error {
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
+            Self::PlayerNotFound {
+                username,
+                platform,
+                message,
+                ..
+            } => {
+                write!(f, "Player {} not found on {}", username, platform.as_str())?;
+                fmt_message(f, message)?;
+                write!(f, ".")
+            }
+            Self::Unauthorized { message, .. } => {
+                write!(f, "Unauthorized, the token is invalid")?;
+                fmt_message(f, message)?;
+                write!(f, ".")
+            }
+            Self::RateLimited {
+                retry_after,
+                message,
+                ..
+            } => {
+                write!(f, "Ratelimited")?;
+                if let Some(after) = retry_after {
+                    write!(f, ", retry after {}s", after.as_secs())?;
+                }
+                fmt_message(f, message)?;
+                write!(f, ".")
+            }
+            Self::ServerError {
+                status, message, ..
+            } => {
+                write!(f, "Server error: {}", status)?;
+                fmt_message(f, message)
+            }
             Self::HttpError(err) => Display::fmt(err, f),
             Self::JsonError(err) => Display::fmt(err, f),
             Self::UsernameMalformed => write!(f, "Username is malformed."),
+            Self::QueryMalformed => write!(f, "Search query is malformed."),
+            Self::UnknownUbisoftId(id) => write!(f, "No username known for Ubisoft ID {}.", id),
+            Self::IoError(err) => Display::fmt(err, f),
             Self::Other(s) => Display::fmt(s, f),
         }
     }
 }
+
+/// Appends the error message sent by the API, if any.
+fn fmt_message(f: &mut Formatter, message: &Option<String>) -> fmt::Result {
+    match message {
+        Some(message) => write!(f, " ({})", message),
+        None => Ok(()),
+    }
+}
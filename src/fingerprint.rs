@@ -0,0 +1,434 @@
+use serde::ser::{self, Serialize};
+use serde_json::Error;
+use std::fmt::{self, Display, Formatter};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// A fingerprint of a model, for detecting changes between two polls.
+///
+/// Equal models have equal fingerprints. The fingerprint is computed from the serialized model
+/// with the keys of maps sorted, so it's the same across runs of the program and can be stored.
+/// Like the equality of models, floats are hashed by their bits, so `NaN` differs from a
+/// missing value.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use r6stats_client::{Client, Platform};
+/// use r6stats_client::Fingerprint;
+///
+/// # async fn run(client: Client) -> Result<(), r6stats_client::Error> {
+/// let stats = client.stats().operators("pengu.g2", Platform::Pc).await?;
+/// let last = Fingerprint::of(&stats);
+///
+/// // Later
+/// let stats = client.stats().operators("pengu.g2", Platform::Pc).await?;
+/// if Fingerprint::of(&stats) != last {
+///     println!("Operator stats of pengu.g2 changed");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Returns the fingerprint of the model.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` can't be serialized, which can't happen for the models of this crate.
+    pub fn of<T: Serialize + ?Sized>(value: &T) -> Self {
+        Self(value.serialize(Hasher).expect("Error serializing model."))
+    }
+
+    /// Returns the fingerprint as an integer.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// The 64-bit FNV-1a hash, which doesn't change between Rust versions like `DefaultHasher`.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new(tag: u8) -> Self {
+        let mut fnv = Self(FNV_OFFSET);
+        fnv.bytes(&[tag]);
+        fnv
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+        self
+    }
+
+    fn u64(&mut self, n: u64) -> &mut Self {
+        self.bytes(&n.to_le_bytes())
+    }
+}
+
+const NULL: u8 = 0;
+const BOOL: u8 = 1;
+const INT: u8 = 2;
+const FLOAT: u8 = 3;
+const STR: u8 = 4;
+const BYTES: u8 = 5;
+const SEQ: u8 = 6;
+const MAP: u8 = 7;
+
+fn hash_str(s: &str) -> u64 {
+    Fnv::new(STR).u64(s.len() as u64).bytes(s.as_bytes()).0
+}
+
+/// Serializes a value into its hash.
+///
+/// Optional values hash like the value itself, and structs like maps, as in JSON.
+struct Hasher;
+
+impl ser::Serializer for Hasher {
+    type Ok = u64;
+    type Error = Error;
+    type SerializeSeq = Seq;
+    type SerializeTuple = Seq;
+    type SerializeTupleStruct = Seq;
+    type SerializeTupleVariant = Variant<Seq>;
+    type SerializeMap = Map;
+    type SerializeStruct = Map;
+    type SerializeStructVariant = Variant<Map>;
+
+    fn serialize_bool(self, v: bool) -> Result<u64, Error> {
+        Ok(Fnv::new(BOOL).bytes(&[v as u8]).0)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<u64, Error> {
+        Ok(Fnv::new(INT).bytes(&v.to_le_bytes()).0)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<u64, Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<u64, Error> {
+        Ok(Fnv::new(INT).bytes(&v.to_le_bytes()).0)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<u64, Error> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<u64, Error> {
+        Ok(Fnv::new(FLOAT).u64(v.to_bits()).0)
+    }
+
+    fn serialize_char(self, v: char) -> Result<u64, Error> {
+        Ok(hash_str(v.encode_utf8(&mut [0; 4])))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<u64, Error> {
+        Ok(hash_str(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<u64, Error> {
+        Ok(Fnv::new(BYTES).u64(v.len() as u64).bytes(v).0)
+    }
+
+    fn serialize_none(self) -> Result<u64, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<u64, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<u64, Error> {
+        Ok(Fnv::new(NULL).0)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<u64, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<u64, Error> {
+        Ok(hash_str(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<u64, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<u64, Error> {
+        let mut map = Map::default();
+        map.entry(hash_str(variant), value.serialize(Hasher)?);
+        Ok(map.finish())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Seq, Error> {
+        Ok(Seq::default())
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Seq, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Seq, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Variant<Seq>, Error> {
+        Ok(Variant {
+            variant,
+            inner: Seq::default(),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Map, Error> {
+        Ok(Map::default())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Map, Error> {
+        Ok(Map::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Variant<Map>, Error> {
+        Ok(Variant {
+            variant,
+            inner: Map::default(),
+        })
+    }
+}
+
+#[derive(Default)]
+struct Seq {
+    hashes: Vec<u64>,
+}
+
+impl Seq {
+    fn finish(self) -> u64 {
+        let mut fnv = Fnv::new(SEQ);
+        fnv.u64(self.hashes.len() as u64);
+        for hash in self.hashes {
+            fnv.u64(hash);
+        }
+        fnv.0
+    }
+}
+
+impl ser::SerializeSeq for Seq {
+    type Ok = u64;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.hashes.push(value.serialize(Hasher)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<u64, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for Seq {
+    type Ok = u64;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<u64, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for Seq {
+    type Ok = u64;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<u64, Error> {
+        Ok(self.finish())
+    }
+}
+
+/// The entries of a map, hashed in the order of their keys.
+#[derive(Default)]
+struct Map {
+    entries: Vec<(u64, u64)>,
+    key: Option<u64>,
+}
+
+impl Map {
+    fn entry(&mut self, key: u64, value: u64) {
+        self.entries.push((key, value));
+    }
+
+    fn finish(mut self) -> u64 {
+        // Maps keep the arbitrary order of the model's `HashMap`s
+        self.entries.sort_unstable();
+
+        let mut fnv = Fnv::new(MAP);
+        fnv.u64(self.entries.len() as u64);
+        for (key, value) in self.entries {
+            fnv.u64(key).u64(value);
+        }
+        fnv.0
+    }
+}
+
+impl ser::SerializeMap for Map {
+    type Ok = u64;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(Hasher)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().expect("Value serialized before its key.");
+        self.entry(key, value.serialize(Hasher)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<u64, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for Map {
+    type Ok = u64;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.entry(hash_str(key), value.serialize(Hasher)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<u64, Error> {
+        Ok(self.finish())
+    }
+}
+
+/// A tuple or struct variant, hashed like a map of the variant to its fields.
+struct Variant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl Variant<Seq> {
+    fn finish(self) -> u64 {
+        let mut map = Map::default();
+        map.entry(hash_str(self.variant), self.inner.finish());
+        map.finish()
+    }
+}
+
+impl Variant<Map> {
+    fn finish(self) -> u64 {
+        let mut map = Map::default();
+        map.entry(hash_str(self.variant), self.inner.finish());
+        map.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Variant<Seq> {
+    type Ok = u64;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<u64, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for Variant<Map> {
+    type Ok = u64;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<u64, Error> {
+        Ok(self.finish())
+    }
+}
@@ -2,81 +2,338 @@
 
 pub(crate) mod error;
 
+mod cache;
+mod headers;
+mod inflight;
+mod priority;
 #[cfg(feature = "ratelimiting")]
 mod ratelimit;
+mod retry;
 
-pub use self::error::Error;
+pub use self::cache::{
+    CacheBuilder, CacheStats, CacheStore, CachedResponse, DiskStore, MemoryStore,
+};
+pub use self::error::{ApiError, Error};
+pub use self::headers::RatelimitHeaders;
+pub use self::priority::Priority;
 #[cfg(feature = "ratelimiting")]
-pub use self::ratelimit::{Ratelimit, RatelimitBuilder};
+pub use self::ratelimit::{
+    FileRatelimit, Ratelimit, RatelimitBackend, RatelimitBuilder, SharedRatelimit, Strategy,
+};
+pub use self::retry::{Jitter, RetryPolicy, RetryPolicyBuilder, RetryableError};
 
-use self::error::{unsuccessful_request, url_error};
-#[cfg(feature = "ratelimiting")]
-use crate::internals::Cell;
+use self::cache::{Cache, Lookup};
+use self::error::unsuccessful_request;
+use self::inflight::{InFlight, Joined, Key};
+use crate::stats::Kind;
 use crate::Error as CrateError;
-use reqwest::{Client, ClientBuilder, Method, Response, StatusCode, Url};
+use bytes::{Bytes, BytesMut};
+use reqwest::header::{
+    HeaderMap, HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Client, Method, Response, StatusCode, Url};
 use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::panic;
+use std::pin::Pin;
+#[cfg(feature = "ratelimiting")]
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::task;
+use tokio::time::delay_for;
+
+/// The maximum number of bytes read from the body of an unsuccessful response.
+const MAX_ERROR_BODY: usize = 16 * 1024;
+
+/// The base URL of the r6stats API.
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api2.r6stats.com/public-api/";
+
+/// An owned dynamically typed future, as returned by [`CacheStore`] and `RatelimitBackend`.
+///
+/// [`CacheStore`]: trait.CacheStore.html
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Runs blocking file system calls on the thread pool for blocking work.
+pub(crate) async fn blocking<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|e| panic::resume_unwind(e.into_panic()))
+}
+
+/// The endpoint a route belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Endpoint {
+    Stats(Kind),
+    Leaderboard,
+    Search,
+}
+
+/// A GET request to a route.
+#[derive(Clone, Debug)]
+pub(crate) struct Request {
+    pub url: Url,
+    pub endpoint: Endpoint,
+    pub priority: Priority,
+    /// Skips the cache, but still caches the response.
+    pub force_refresh: bool,
+}
 
 pub(crate) struct Http {
     client: Client,
     token: String,
+    base_url: Url,
+    retry: RetryPolicy,
+    cache: Option<Cache>,
+    in_flight: InFlight,
     #[cfg(feature = "ratelimiting")]
-    ratelimit: Cell<Ratelimit>,
+    ratelimit: Arc<dyn RatelimitBackend>,
 }
 
 impl Http {
     pub fn new(
         token: &str,
-        #[cfg(feature = "ratelimiting")] ratelimit: Ratelimit,
-    ) -> Result<Self, CrateError> {
+        base_url: Url,
+        client: Client,
+        retry: RetryPolicy,
+        cache: Option<Cache>,
+        #[cfg(feature = "ratelimiting")] ratelimit: Arc<dyn RatelimitBackend>,
+    ) -> Self {
         let token = token.trim();
-        let token = if token.starts_with("Bearer ") {
-            token[7..].to_string()
-        } else {
-            token.to_string()
-        };
+        let token = token.strip_prefix("Bearer ").unwrap_or(token).to_string();
 
-        let client = ClientBuilder::new().use_rustls_tls().build()?;
-
-        Ok(Self {
+        Self {
             client,
             token,
+            base_url,
+            retry,
+            cache,
+            in_flight: InFlight::default(),
             #[cfg(feature = "ratelimiting")]
-            ratelimit: Cell::new(ratelimit),
-        })
+            ratelimit,
+        }
+    }
+
+    /// The base URL all routes are joined onto.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
     }
 
     #[cfg(feature = "ratelimiting")]
-    pub async fn ratelimit(&self) -> Ratelimit {
-        *borrow!(self.ratelimit)
+    pub async fn ratelimit(&self) -> Result<Ratelimit, CrateError> {
+        self.ratelimit.ratelimit().await
+    }
+
+    pub async fn cache_stats(&self) -> Option<CacheStats> {
+        match &self.cache {
+            Some(cache) => Some(cache.stats().await),
+            None => None,
+        }
     }
 
-    pub async fn request(&self, path: &str) -> Result<Response, CrateError> {
-        let url = Url::parse(path).map_err(|e| url_error(path, e))?;
+    /// Sends a GET request and returns the response body, answering it from the cache if
+    /// possible.
+    ///
+    /// Outdated responses are revalidated with a conditional request, if the server sent
+    /// validators along with them. Concurrent requests to the same route share one response, unless
+    /// they skip the cache or have another priority.
+    pub async fn request(&self, request: &Request) -> Result<Bytes, CrateError> {
+        let key = request.url.as_str();
+        let mut stale = None;
+
+        if let Some(cache) = &self.cache {
+            if !request.force_refresh {
+                match cache.get(key, request.endpoint).await {
+                    Lookup::Fresh(body) => return Ok(body),
+                    Lookup::Stale(cached) => stale = Some(cached),
+                    Lookup::Miss => {}
+                }
+            }
+        }
+
+        loop {
+            match self.in_flight.join(Key::of(request)) {
+                Joined::Leader(leader) => {
+                    let result = match self.fetch(request, stale.as_ref()).await {
+                        Ok(response) => {
+                            let body = response.body.clone();
+                            if let Some(cache) = &self.cache {
+                                cache.insert(key, request.endpoint, response).await;
+                            }
+                            Ok(body)
+                        }
+                        Err(err) => Err(err),
+                    };
+
+                    leader.complete(&result);
+                    return result;
+                }
+                Joined::Follower(rx) => {
+                    // Otherwise the request was cancelled, and is sent again
+                    if let Ok(outcome) = rx.await {
+                        return outcome;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sends a GET request, retrying it according to the [`RetryPolicy`].
+    async fn fetch(
+        &self,
+        request: &Request,
+        stale: Option<&CachedResponse>,
+    ) -> Result<CachedResponse, CrateError> {
+        let mut attempt = 1;
+
+        loop {
+            let err = match self.send(request, stale).await {
+                Ok(response) => return Ok(response),
+                Err(err) => err,
+            };
 
+            if attempt >= self.retry.max_attempts() || !self.retry.is_retryable(&err) {
+                return Err(err);
+            }
+
+            // A server-side 429 means the quota is used up, so the next attempt has to
+            // wait for the ratelimit reset instead of the backoff, unless the server said
+            // how long to wait.
+            #[cfg(feature = "ratelimiting")]
+            let waits_for_reset = if is_rate_limited(&err) && retry_after(&err).is_none() {
+                match self.ratelimit.exhaust().await {
+                    Ok(waits) => waits,
+                    // Like in `send`, the error of the backend is ignored: the 429 tells the
+                    // caller more, and the next `acquire` reports a broken backend anyway.
+                    Err(_) => return Err(err),
+                }
+            } else {
+                false
+            };
+            #[cfg(not(feature = "ratelimiting"))]
+            let waits_for_reset = false;
+
+            if !waits_for_reset {
+                let delay = retry_after(&err)
+                    .map(|delay| delay.min(self.retry.max_backoff()))
+                    .unwrap_or_else(|| self.retry.backoff(attempt));
+                delay_for(delay).await;
+            }
+
+            attempt += 1;
+        }
+    }
+
+    /// Sends a GET request, made conditional on the validators of the `stale` response.
+    ///
+    /// Returns the `stale` response if the server confirmed it's still up to date.
+    async fn send(
+        &self,
+        request: &Request,
+        stale: Option<&CachedResponse>,
+    ) -> Result<CachedResponse, CrateError> {
         #[cfg(feature = "ratelimiting")]
-        borrow_mut!(self.ratelimit).pre_hook().await?;
+        self.ratelimit.acquire(request.priority).await?;
 
-        let response = self
+        let url = &request.url;
+        let mut builder = self
             .client
-            .request(Method::GET, url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+            .request(Method::GET, url.clone())
+            .bearer_auth(&self.token);
+
+        if let Some(stale) = stale {
+            if let Some(etag) = &stale.etag {
+                builder = builder.header(IF_NONE_MATCH, etag.as_str());
+            }
+            if let Some(last_modified) = &stale.last_modified {
+                builder = builder.header(IF_MODIFIED_SINCE, last_modified.as_str());
+            }
+        }
+
+        let mut response = builder.send().await?;
+
+        let headers = RatelimitHeaders::from_headers(response.headers());
+
+        // The response is still valid if the backend failed to record the headers, so the
+        // error is ignored. The next `acquire` reports a broken backend anyway.
+        #[cfg(feature = "ratelimiting")]
+        let _ = self.ratelimit.update(headers).await;
 
         let status = response.status();
+        if let (StatusCode::NOT_MODIFIED, Some(stale)) = (status, stale) {
+            return Ok(stale.revalidated());
+        }
         if status != StatusCode::OK {
-            return Err(unsuccessful_request(path, status));
+            let body = read_bounded(&mut response, MAX_ERROR_BODY).await;
+            return Err(unsuccessful_request(
+                url.as_str(),
+                status,
+                headers.retry_after,
+                body,
+            ));
+        }
+
+        let etag = header_str(response.headers(), &ETAG);
+        let last_modified = header_str(response.headers(), &LAST_MODIFIED);
+
+        let mut cached = CachedResponse::new(response.bytes().await?, SystemTime::now());
+        cached.etag = etag;
+        cached.last_modified = last_modified;
+
+        Ok(cached)
+    }
+}
+
+/// Reads at most `limit` bytes of the body, ignoring failures since the body is only used to
+/// describe an error.
+async fn read_bounded(response: &mut Response, limit: usize) -> Bytes {
+    let mut body = BytesMut::new();
+
+    while body.len() < limit {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                let len = chunk.len().min(limit - body.len());
+                body.extend_from_slice(&chunk[..len]);
+            }
+            Ok(None) | Err(_) => break,
         }
+    }
+
+    body.freeze()
+}
+
+fn header_str(headers: &HeaderMap, name: &HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
 
-        Ok(response)
+fn retry_after(err: &CrateError) -> Option<std::time::Duration> {
+    match err {
+        CrateError::RateLimited { retry_after, .. } => *retry_after,
+        CrateError::HttpError(err) => err.retry_after(),
+        _ => None,
     }
 }
 
+#[cfg(feature = "ratelimiting")]
+fn is_rate_limited(err: &CrateError) -> bool {
+    matches!(err, CrateError::RateLimited { .. })
+}
+
 impl Debug for Http {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut d = f.debug_struct("Http");
 
         d.field("client", &self.client);
+        d.field("base_url", &self.base_url);
+        d.field("retry", &self.retry);
+        d.field("cache", &self.cache);
+        d.field("in_flight", &self.in_flight);
 
         #[cfg(feature = "ratelimiting")]
         d.field("ratelimit", &self.ratelimit);
@@ -87,7 +344,46 @@ impl Debug for Http {
 
 #[cfg(test)]
 mod tests {
-    use super::{Http, Ratelimit};
+    use super::cache::Cache;
+    use super::{
+        CacheBuilder, Endpoint, Http, Priority, Request, RetryPolicy, DEFAULT_BASE_URL,
+        MAX_ERROR_BODY,
+    };
+    #[cfg(feature = "ratelimiting")]
+    use super::{Ratelimit, SharedRatelimit};
+    use crate::test_util::{MockResponse, MockServer};
+    use crate::Error as CrateError;
+    use futures::future::join_all;
+    use reqwest::{Client, StatusCode, Url};
+    use std::time::Duration;
+    use tokio::time::{delay_for, timeout};
+
+    const LAST_MODIFIED: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+    fn http(token: &str) -> Http {
+        http_with_cache(token, None)
+    }
+
+    fn http_with_cache(token: &str, cache: Option<Cache>) -> Http {
+        Http::new(
+            token,
+            Url::parse(DEFAULT_BASE_URL).unwrap(),
+            Client::new(),
+            RetryPolicy::default(),
+            cache,
+            #[cfg(feature = "ratelimiting")]
+            std::sync::Arc::new(SharedRatelimit::from_ratelimit(Ratelimit::default())),
+        )
+    }
+
+    fn request(url: Url) -> Request {
+        Request {
+            url,
+            endpoint: Endpoint::Leaderboard,
+            priority: Priority::Normal,
+            force_refresh: false,
+        }
+    }
 
     #[test]
     fn test_token_formatting() {
@@ -95,22 +391,292 @@ mod tests {
         let tokens = ["ABC", "  ABC  ", "Bearer ABC", "  Bearer ABC "];
 
         for &token in tokens.iter() {
-            let http = Http::new(token, Ratelimit::default()).unwrap();
+            let http = http(token);
             assert_eq!(http.token, expected);
         }
     }
 
     #[tokio::test]
     async fn test_request() {
-        let http = Http::new("", Ratelimit::default()).unwrap();
+        let server = MockServer::start().await;
+        server.mount("/status/200", MockResponse::status(200));
+        server.mount("/status/404", MockResponse::status(404));
+
+        let http = http("TOKEN");
+        let base = Url::parse(&server.base_url()).unwrap();
 
         let _ = http
-            .request("https://httpbin.org/status/200")
+            .request(&request(base.join("status/200").unwrap()))
             .await
             .unwrap();
-        let _ = http
-            .request("https://httpbin.org/status/404")
+        let err = http
+            .request(&request(base.join("status/404").unwrap()))
+            .await
+            .unwrap_err();
+
+        match err {
+            CrateError::HttpError(err) => {
+                assert_eq!(err.unsuccessful_request(), Some(StatusCode::NOT_FOUND));
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r.token.as_deref() == Some("TOKEN")));
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let server = MockServer::start().await;
+        server.mount("/cached", MockResponse::json("{}"));
+
+        let http = http_with_cache("TOKEN", Some(CacheBuilder::default().build()));
+        let base = Url::parse(&server.base_url()).unwrap();
+        let mut request = request(base.join("cached").unwrap());
+
+        for _ in 0..3 {
+            assert_eq!(http.request(&request).await.unwrap(), "{}");
+        }
+        assert_eq!(server.requests().len(), 1);
+
+        request.force_refresh = true;
+        http.request(&request).await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+
+        let stats = http.cache_stats().await.unwrap();
+        assert_eq!(stats.hits(), 2);
+        assert_eq!(stats.misses(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_revalidation() {
+        let server = MockServer::start().await;
+        server.mount_sequence(
+            "/etag",
+            vec![
+                MockResponse::json("{}").header("ETag", "\"v1\""),
+                MockResponse::status(304),
+            ],
+        );
+        server.mount_sequence(
+            "/last-modified",
+            vec![
+                MockResponse::json("{}").header("Last-Modified", LAST_MODIFIED),
+                MockResponse::status(304),
+            ],
+        );
+
+        let cache = CacheBuilder::default()
+            .ttl(Duration::from_millis(10))
+            .build();
+        let http = http_with_cache("TOKEN", Some(cache));
+        let base = Url::parse(&server.base_url()).unwrap();
+
+        for path in ["etag", "last-modified"].iter() {
+            let request = request(base.join(path).unwrap());
+
+            assert_eq!(http.request(&request).await.unwrap(), "{}");
+            delay_for(Duration::from_millis(20)).await;
+            assert_eq!(http.request(&request).await.unwrap(), "{}");
+
+            // Revalidated responses are fresh again
+            assert_eq!(http.request(&request).await.unwrap(), "{}");
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].header("If-None-Match"), None);
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+        assert_eq!(requests[3].header("If-Modified-Since"), Some(LAST_MODIFIED));
+        assert_eq!(http.cache_stats().await.unwrap().hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_coalescing() {
+        let server = MockServer::start().await;
+        let delay = Duration::from_millis(50);
+        server.mount("/ok", MockResponse::json("{}").delay(delay));
+        server.mount("/404", MockResponse::status(404).delay(delay));
+
+        let http = http("TOKEN");
+        let base = Url::parse(&server.base_url()).unwrap();
+        let ok = request(base.join("ok").unwrap());
+        let not_found = request(base.join("404").unwrap());
+
+        let (a, b, c) = tokio::join!(http.request(&ok), http.request(&ok), http.request(&ok));
+        assert_eq!(a.unwrap(), "{}");
+        assert_eq!(b.unwrap(), "{}");
+        assert_eq!(c.unwrap(), "{}");
+        assert_eq!(server.requests().len(), 1);
+
+        // Errors of the response are shared as well
+        let (a, b) = tokio::join!(http.request(&not_found), http.request(&not_found));
+        assert!(a.is_err() && b.is_err());
+        assert_eq!(server.requests().len(), 2);
+
+        // Sequential requests aren't coalesced
+        http.request(&ok).await.unwrap();
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_key() {
+        let server = MockServer::start().await;
+        server.mount(
+            "/ok",
+            MockResponse::json("{}").delay(Duration::from_millis(50)),
+        );
+
+        let http = http("TOKEN");
+        let ok = request(Url::parse(&server.base_url()).unwrap().join("ok").unwrap());
+        let refresh = Request {
+            force_refresh: true,
+            ..ok.clone()
+        };
+        let high = Request {
+            priority: Priority::High,
+            ..ok.clone()
+        };
+
+        let (a, b, c) = tokio::join!(
+            http.request(&ok),
+            http.request(&refresh),
+            http.request(&high)
+        );
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_request_error() {
+        let server = MockServer::start().await;
+        server.mount(
+            "/slow",
+            MockResponse::json("{}").delay(Duration::from_millis(200)),
+        );
+
+        let client = Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let http = Http::new(
+            "TOKEN",
+            Url::parse(DEFAULT_BASE_URL).unwrap(),
+            client,
+            RetryPolicy::default(),
+            None,
+            #[cfg(feature = "ratelimiting")]
+            std::sync::Arc::new(SharedRatelimit::from_ratelimit(Ratelimit::default())),
+        );
+        let slow = request(
+            Url::parse(&server.base_url())
+                .unwrap()
+                .join("slow")
+                .unwrap(),
+        );
+
+        let results = join_all((0..10).map(|_| http.request(&slow))).await;
+
+        // The timeout of the leader is handed to every caller, instead of each retrying
+        for result in results {
+            match result {
+                Err(CrateError::HttpError(err)) => {
+                    assert!(err.request_error().unwrap().is_timeout())
+                }
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_cancelled_leader() {
+        let server = MockServer::start().await;
+        server.mount(
+            "/ok",
+            MockResponse::json("{}").delay(Duration::from_millis(50)),
+        );
+
+        let http = http("TOKEN");
+        let base = Url::parse(&server.base_url()).unwrap();
+        let ok = request(base.join("ok").unwrap());
+
+        let (leader, follower) = tokio::join!(
+            timeout(Duration::from_millis(10), http.request(&ok)),
+            http.request(&ok)
+        );
+        assert!(leader.is_err());
+        assert_eq!(follower.unwrap(), "{}");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_not_modified_without_cache() {
+        let server = MockServer::start().await;
+        server.mount("/etag", MockResponse::status(304));
+
+        let http = http("TOKEN");
+        let base = Url::parse(&server.base_url()).unwrap();
+
+        let err = http
+            .request(&request(base.join("etag").unwrap()))
             .await
             .unwrap_err();
+
+        match err {
+            CrateError::HttpError(err) => {
+                assert_eq!(err.unsuccessful_request(), Some(StatusCode::NOT_MODIFIED));
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_body() {
+        let server = MockServer::start().await;
+        server.mount(
+            "/status/400",
+            MockResponse::status(400).body(r#"{"status":"error","message":"Invalid platform"}"#),
+        );
+        server.mount(
+            "/large",
+            MockResponse::status(400).body(vec![b'a'; 2 * MAX_ERROR_BODY]),
+        );
+
+        let http = http("TOKEN");
+        let base = Url::parse(&server.base_url()).unwrap();
+
+        let err = http
+            .request(&request(base.join("status/400").unwrap()))
+            .await
+            .unwrap_err();
+
+        match err {
+            CrateError::HttpError(err) => {
+                let api_error = err.api_error().unwrap();
+                assert_eq!(api_error.status.as_deref(), Some("error"));
+                assert_eq!(api_error.message, "Invalid platform");
+                assert!(err.body().unwrap().starts_with(b"{"));
+                assert_eq!(
+                    err.to_string(),
+                    "Unsuccessful request: 400 Bad Request (Invalid platform)"
+                );
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        let err = http
+            .request(&request(base.join("large").unwrap()))
+            .await
+            .unwrap_err();
+
+        match err {
+            CrateError::HttpError(err) => {
+                assert!(err.api_error().is_none());
+                assert_eq!(err.body().unwrap().len(), MAX_ERROR_BODY);
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
     }
 }
@@ -0,0 +1,318 @@
+//! Module for caching responses.
+
+mod disk;
+mod store;
+
+pub use self::disk::DiskStore;
+pub use self::store::{CacheStore, CachedResponse, MemoryStore};
+
+use super::Endpoint;
+use crate::stats::Kind;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_CAPACITY: usize = 256;
+const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// The builder for the response cache.
+///
+/// ## Example
+/// ```rust
+/// use r6stats_client::stats::Kind;
+/// use r6stats_client::Client;
+/// use std::time::Duration;
+///
+/// let client = Client::builder("TOKEN")
+///     .cache(|c| {
+///         c.capacity(1000)
+///             .stats_ttl(Kind::Seasonal, Duration::from_secs(30 * 60))
+///             .leaderboard_ttl(Duration::from_secs(60))
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CacheBuilder {
+    capacity: Option<usize>,
+    ttl: Option<Duration>,
+    stats_ttls: HashMap<Kind, Duration>,
+    leaderboard_ttl: Option<Duration>,
+    search_ttl: Option<Duration>,
+    store: Option<Arc<dyn CacheStore>>,
+}
+
+impl CacheBuilder {
+    /// Sets the maximum number of cached responses.
+    ///
+    /// When the cache is full, the least recently used response is evicted. Defaults to `256`.
+    ///
+    /// Has no effect if a custom [`store`] is set.
+    ///
+    /// [`store`]: #method.store
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the time responses are served from the cache, unless overridden for the endpoint.
+    ///
+    /// Defaults to 5 minutes.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the time responses of the stats endpoint of the [`Kind`] are served from the cache.
+    ///
+    /// Set to zero to disable caching for the [`Kind`].
+    ///
+    /// [`Kind`]: ../stats/enum.Kind.html
+    pub fn stats_ttl(mut self, kind: Kind, ttl: Duration) -> Self {
+        self.stats_ttls.insert(kind, ttl);
+        self
+    }
+
+    /// Sets the time responses of the leaderboard endpoint are served from the cache.
+    ///
+    /// Set to zero to disable caching for the leaderboard.
+    pub fn leaderboard_ttl(mut self, ttl: Duration) -> Self {
+        self.leaderboard_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the time responses of the player search endpoint are served from the cache.
+    ///
+    /// Set to zero to disable caching for player searches.
+    pub fn search_ttl(mut self, ttl: Duration) -> Self {
+        self.search_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the [`CacheStore`] holding the cached responses, e.g. a [`DiskStore`].
+    ///
+    /// Defaults to a [`MemoryStore`].
+    ///
+    /// [`CacheStore`]: trait.CacheStore.html
+    /// [`DiskStore`]: struct.DiskStore.html
+    /// [`MemoryStore`]: struct.MemoryStore.html
+    pub fn store(mut self, store: impl CacheStore + 'static) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
+
+    /// Returns the finished cache.
+    pub(crate) fn build(self) -> Cache {
+        let ttl = self.ttl.unwrap_or(DEFAULT_TTL);
+        let capacity = self.capacity.unwrap_or(DEFAULT_CAPACITY);
+
+        Cache {
+            ttl,
+            stats_ttls: self.stats_ttls,
+            leaderboard_ttl: self.leaderboard_ttl.unwrap_or(ttl),
+            search_ttl: self.search_ttl.unwrap_or(ttl),
+            store: self
+                .store
+                .unwrap_or_else(|| Arc::new(MemoryStore::new(capacity))),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Counters of the response cache.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+    len: usize,
+}
+
+impl CacheStats {
+    /// The number of requests answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of requests which weren't cached, or whose cached response was outdated.
+    ///
+    /// Requests skipping the cache aren't counted.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The number of responses evicted to make room for newer ones.
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+
+    /// The number of cached responses.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no responses are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// The result of looking up a route in the [`Cache`].
+///
+/// [`Cache`]: struct.Cache.html
+#[derive(Debug)]
+pub(crate) enum Lookup {
+    /// The cached response is fresh and can be served right away.
+    Fresh(Bytes),
+    /// The cached response is outdated, but can be revalidated with the server.
+    Stale(CachedResponse),
+    Miss,
+}
+
+/// Caches responses by route.
+#[derive(Debug)]
+pub(crate) struct Cache {
+    ttl: Duration,
+    stats_ttls: HashMap<Kind, Duration>,
+    leaderboard_ttl: Duration,
+    search_ttl: Duration,
+    store: Arc<dyn CacheStore>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Cache {
+    /// The time responses of the endpoint are served from the cache.
+    pub fn ttl(&self, endpoint: Endpoint) -> Duration {
+        match endpoint {
+            Endpoint::Stats(kind) => self.stats_ttls.get(&kind).copied().unwrap_or(self.ttl),
+            Endpoint::Leaderboard => self.leaderboard_ttl,
+            Endpoint::Search => self.search_ttl,
+        }
+    }
+
+    /// Looks up the cached response of the route.
+    pub async fn get(&self, key: &str, endpoint: Endpoint) -> Lookup {
+        let ttl = self.ttl(endpoint);
+        if ttl == Duration::from_secs(0) {
+            return Lookup::Miss;
+        }
+
+        let lookup = match self.store.get(key).await.ok().flatten() {
+            Some(cached) if cached.is_fresh(ttl) => Lookup::Fresh(cached.body),
+            Some(cached) if cached.has_validators() => Lookup::Stale(cached),
+            _ => Lookup::Miss,
+        };
+
+        let counter = match lookup {
+            Lookup::Fresh(_) => &self.hits,
+            _ => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        lookup
+    }
+
+    /// Caches the response, unless caching is disabled for the endpoint.
+    pub async fn insert(&self, key: &str, endpoint: Endpoint, response: CachedResponse) {
+        if self.ttl(endpoint) == Duration::from_secs(0) {
+            return;
+        }
+
+        // A failure to cache the response mustn't fail the request
+        let _ = self.store.insert(key, response).await;
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.store.evictions().await,
+            len: self.store.len().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cache, CacheBuilder, CachedResponse, Lookup};
+    use crate::http::Endpoint;
+    use crate::stats::Kind;
+    use bytes::Bytes;
+    use std::time::{Duration, SystemTime};
+
+    const GENERIC: Endpoint = Endpoint::Stats(Kind::Generic);
+
+    fn response(s: &'static str) -> CachedResponse {
+        CachedResponse::new(Bytes::from_static(s.as_bytes()), SystemTime::now())
+    }
+
+    async fn fresh(cache: &Cache, key: &str, endpoint: Endpoint) -> Option<Bytes> {
+        match cache.get(key, endpoint).await {
+            Lookup::Fresh(body) => Some(body),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hits_and_misses() {
+        let cache = CacheBuilder::default().build();
+
+        assert_eq!(fresh(&cache, "a", GENERIC).await, None);
+        cache.insert("a", GENERIC, response("a")).await;
+        assert_eq!(
+            fresh(&cache, "a", GENERIC).await,
+            Some(Bytes::from_static(b"a"))
+        );
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.hits(), 1);
+        assert_eq!(stats.misses(), 1);
+        assert_eq!(stats.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ttls() {
+        let cache = CacheBuilder::default()
+            .ttl(Duration::from_secs(60))
+            .stats_ttl(Kind::Seasonal, Duration::from_secs(600))
+            .stats_ttl(Kind::Weapons, Duration::from_secs(0))
+            .build();
+
+        assert_eq!(cache.ttl(GENERIC), Duration::from_secs(60));
+        assert_eq!(cache.ttl(Endpoint::Leaderboard), Duration::from_secs(60));
+        assert_eq!(cache.ttl(Endpoint::Search), Duration::from_secs(60));
+        assert_eq!(
+            cache.ttl(Endpoint::Stats(Kind::Seasonal)),
+            Duration::from_secs(600)
+        );
+
+        // Caching disabled
+        let weapons = Endpoint::Stats(Kind::Weapons);
+        cache.insert("w", weapons, response("w")).await;
+        assert_eq!(fresh(&cache, "w", weapons).await, None);
+        assert!(cache.stats().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired() {
+        let cache = CacheBuilder::default()
+            .ttl(Duration::from_millis(20))
+            .build();
+
+        let mut validated = response("b");
+        validated.etag = Some("\"b\"".to_string());
+
+        cache.insert("a", GENERIC, response("a")).await;
+        cache.insert("b", GENERIC, validated).await;
+        tokio::time::delay_for(Duration::from_millis(30)).await;
+
+        assert!(matches!(cache.get("a", GENERIC).await, Lookup::Miss));
+        // Outdated responses with validators can be revalidated
+        assert!(matches!(cache.get("b", GENERIC).await, Lookup::Stale(_)));
+        assert_eq!(cache.stats().await.misses(), 2);
+    }
+}
@@ -0,0 +1,491 @@
+//! Module for keeping cached responses on disk.
+
+use super::store::{CacheStore, CachedResponse};
+use crate::http::{blocking, BoxFuture};
+use crate::Error;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::{self, DirEntry, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Only files with this extension are read, counted and evicted by the store.
+const EXTENSION: &str = "r6stats";
+const TMP_EXTENSION: &str = "tmp";
+
+/// Temporary files older than this were left behind by a crashed process.
+const STALE_TMP_AGE: Duration = Duration::from_secs(60);
+
+/// Distinguishes the temporary files of concurrent writes within a process.
+static WRITE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Keeps cached responses on disk, so they survive restarts of the process.
+///
+/// Every route is stored as a file in the given directory, which is created when the first
+/// response is stored. The files are named by a hash of the route with the `.r6stats`
+/// extension, and hold the metadata of the response as a line of JSON followed by the raw body.
+/// Other files in the directory are left alone. Unreadable files are treated as cache misses
+/// and replaced by the next response, and temporary files left behind by a crash are removed.
+///
+/// Responses are never evicted, unless a limit is set with [`max_entries`].
+///
+/// The files are read and written on the thread pool for blocking work of the runtime.
+///
+/// ## Example
+/// ```rust
+/// use r6stats_client::http::DiskStore;
+/// use r6stats_client::Client;
+///
+/// let client = Client::builder("TOKEN")
+///     .cache(|c| c.store(DiskStore::new("/tmp/r6stats-cache")))
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// [`max_entries`]: #method.max_entries
+#[derive(Clone, Debug)]
+pub struct DiskStore {
+    dir: PathBuf,
+    max_entries: Option<usize>,
+    /// Whether the temporary files left behind by crashes were removed.
+    cleaned_up: Arc<AtomicBool>,
+    evictions: Arc<AtomicU64>,
+}
+
+impl DiskStore {
+    /// Returns a store keeping its files in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_entries: None,
+            cleaned_up: Arc::new(AtomicBool::new(false)),
+            evictions: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Limits the number of stored responses.
+    ///
+    /// When a response is stored beyond the limit, the least recently used ones are evicted.
+    /// Files are used when they're read or written, as recorded by their modification time.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// The directory the responses are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Routes can be longer than the file names allowed by the file system
+        self.dir.join(format!("{:016x}.{}", hash(key), EXTENSION))
+    }
+
+    fn read(&self, key: &str) -> Result<Option<CachedResponse>, Error> {
+        let path = self.path(key);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        // The route is compared in case another one has the same hash
+        let response = Header::split(&bytes)
+            .filter(|(header, _)| header.key == key)
+            .map(|(header, body)| header.into_response(body));
+
+        if response.is_some() && self.max_entries.is_some() {
+            let _ = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+        }
+
+        Ok(response)
+    }
+
+    fn write(&self, key: &str, response: &CachedResponse) -> Result<(), Error> {
+        let path = self.path(key);
+
+        // Written to a temporary file first, so the response is never seen half-written
+        let mut tmp_path = OsString::from(path.as_os_str());
+        tmp_path.push(format!(
+            ".{}.{}.{}",
+            process::id(),
+            WRITE_ID.fetch_add(1, Ordering::Relaxed),
+            TMP_EXTENSION
+        ));
+
+        fs::create_dir_all(&self.dir)?;
+        if !self.cleaned_up.swap(true, Ordering::Relaxed) {
+            self.remove_stale_tmp_files();
+        }
+
+        let result = File::create(&tmp_path)
+            .and_then(|mut file| {
+                let mut header = serde_json::to_vec(&Header::new(key, response))?;
+                header.push(b'\n');
+                file.write_all(&header)?;
+                file.write_all(&response.body)
+            })
+            .and_then(|()| fs::rename(&tmp_path, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result?;
+
+        if let Some(max_entries) = self.max_entries {
+            self.evict(max_entries);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the least recently used responses beyond `max_entries`.
+    fn evict(&self, max_entries: usize) {
+        let mut entries: Vec<_> = self
+            .entries()
+            .map(|entry| {
+                let modified = entry.metadata().and_then(|m| m.modified());
+                (modified.unwrap_or(UNIX_EPOCH), entry.path())
+            })
+            .collect();
+        if entries.len() <= max_entries {
+            return;
+        }
+
+        entries.sort_unstable();
+        let excess = entries.len() - max_entries;
+        for (_, path) in entries.into_iter().take(excess) {
+            // Another process sharing the directory might have removed it first
+            if fs::remove_file(path).is_ok() {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes the temporary files of writes which never finished.
+    ///
+    /// Recent files are kept, since other processes might still be writing them.
+    fn remove_stale_tmp_files(&self) {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let is_stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > STALE_TMP_AGE);
+
+            if path.extension().is_some_and(|ext| ext == TMP_EXTENSION) && is_stale {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    /// Returns the files of the stored responses.
+    fn entries(&self) -> impl Iterator<Item = DirEntry> {
+        fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| is_response_file(&entry.path()))
+    }
+
+    fn count(&self) -> usize {
+        self.entries().count()
+    }
+}
+
+/// Returns true if the file was written by a [`DiskStore`], named like `<hash>.r6stats`.
+///
+/// [`DiskStore`]: struct.DiskStore.html
+fn is_response_file(path: &Path) -> bool {
+    let stem = path.file_stem().and_then(|stem| stem.to_str());
+    path.extension().is_some_and(|ext| ext == EXTENSION)
+        && stem.is_some_and(|stem| stem.len() == 16 && stem.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+impl CacheStore for DiskStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<CachedResponse>, Error>> {
+        let store = self.clone();
+        let key = key.to_string();
+        Box::pin(blocking(move || store.read(&key)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        key: &'a str,
+        response: CachedResponse,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        let store = self.clone();
+        let key = key.to_string();
+        Box::pin(blocking(move || store.write(&key, &response)))
+    }
+
+    fn len(&self) -> BoxFuture<'_, usize> {
+        let store = self.clone();
+        Box::pin(blocking(move || store.count()))
+    }
+
+    fn evictions(&self) -> BoxFuture<'_, u64> {
+        let evictions = self.evictions.load(Ordering::Relaxed);
+        Box::pin(async move { evictions })
+    }
+}
+
+/// Returns the 64-bit FNV-1a hash of the route's bytes, which names its file.
+///
+/// The hash is part of the layout of the directory, so it must never change: unlike
+/// `DefaultHasher`, FNV-1a is fully specified and the same on every platform and Rust version.
+fn hash(key: &str) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    key.bytes().fold(OFFSET, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// The metadata of a stored response, written as a line of JSON before the raw body.
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    /// The route of the response.
+    key: String,
+    /// Milliseconds since the unix epoch.
+    fetched_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+    /// The length of the body, to detect files cut off by a crash.
+    len: usize,
+}
+
+impl Header {
+    fn new(key: &str, response: &CachedResponse) -> Self {
+        let fetched_at = response
+            .fetched_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        Self {
+            key: key.to_string(),
+            fetched_at,
+            etag: response.etag.clone(),
+            last_modified: response.last_modified.clone(),
+            len: response.body.len(),
+        }
+    }
+
+    /// Splits a stored file into its header and body, returning `None` if it's corrupt.
+    fn split(bytes: &[u8]) -> Option<(Self, &[u8])> {
+        // Compact JSON escapes every newline, so the first one ends the header
+        let end = bytes.iter().position(|&b| b == b'\n')?;
+        let header: Self = serde_json::from_slice(&bytes[..end]).ok()?;
+        let body = &bytes[end + 1..];
+
+        if body.len() == header.len {
+            Some((header, body))
+        } else {
+            None
+        }
+    }
+
+    fn into_response(self, body: &[u8]) -> CachedResponse {
+        let mut response = CachedResponse::new(
+            Bytes::copy_from_slice(body),
+            UNIX_EPOCH + Duration::from_millis(self.fetched_at),
+        );
+        response.etag = self.etag;
+        response.last_modified = self.last_modified;
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash, DiskStore};
+    use crate::http::{CacheStore, CachedResponse};
+    use bytes::Bytes;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::time::{Duration, SystemTime};
+
+    fn response() -> CachedResponse {
+        CachedResponse::new(Bytes::from_static(b"{}"), SystemTime::now())
+    }
+
+    fn store_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("r6stats_client-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    const KEY: &str = "https://api2.r6stats.com/public-api/stats/pengu.g2/pc/generic";
+
+    #[tokio::test]
+    async fn test_survives_restart() {
+        let dir = store_dir("restart");
+        let fetched_at = SystemTime::now() - Duration::from_secs(60);
+        let mut response = CachedResponse::new(Bytes::from_static(b"{}"), fetched_at);
+        response.etag = Some("\"abc\"".to_string());
+
+        DiskStore::new(&dir).insert(KEY, response).await.unwrap();
+
+        let store = DiskStore::new(&dir);
+        let cached = store.get(KEY).await.unwrap().unwrap();
+        assert_eq!(cached.body, Bytes::from_static(b"{}"));
+        assert!(cached.fetched_at.elapsed().unwrap() >= Duration::from_secs(60));
+        assert_eq!(cached.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(cached.last_modified, None);
+        assert!(store.get("other").await.unwrap().is_none());
+        assert_eq!(store.len().await, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_file() {
+        let dir = store_dir("corrupt");
+        let store = DiskStore::new(&dir);
+        let response = CachedResponse::new(Bytes::from_static(b"{}"), SystemTime::now());
+
+        store.insert(KEY, response.clone()).await.unwrap();
+        let path = store.path(KEY);
+
+        // Cut off, as if the process crashed while writing
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(store.get(KEY).await.unwrap().is_none());
+
+        store.insert(KEY, response).await.unwrap();
+        assert!(store.get(KEY).await.unwrap().is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        // Changing the hash would orphan every file stored by earlier versions
+        assert_eq!(hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash("foobar"), 0x85944171f73967e8);
+    }
+
+    #[tokio::test]
+    async fn test_long_key() {
+        let dir = store_dir("long");
+        let store = DiskStore::new(&dir);
+        let key = format!("{}/{}", KEY, "a".repeat(1000));
+
+        store.insert(&key, response()).await.unwrap();
+
+        assert!(store.get(&key).await.unwrap().is_some());
+        assert!(store.get(KEY).await.unwrap().is_none());
+        assert_eq!(store.len().await, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_removes_stale_tmp_files() {
+        let dir = store_dir("tmp");
+        fs::create_dir_all(&dir).unwrap();
+
+        // Left behind by a crashed process, and by one still writing
+        let stale = dir.join("0123456789abcdef.r6stats.1.0.tmp");
+        let recent = dir.join("0123456789abcdef.r6stats.2.0.tmp");
+        fs::File::create(&stale)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(600))
+            .unwrap();
+        fs::File::create(&recent).unwrap();
+
+        DiskStore::new(&dir).insert(KEY, response()).await.unwrap();
+
+        assert!(!stale.exists());
+        assert!(recent.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_binary_body() {
+        let dir = store_dir("binary");
+        let store = DiskStore::new(&dir);
+        let body = Bytes::from_static(b"\xff\x00\n{\"a\":\n1}\xfe");
+
+        store
+            .insert(KEY, CachedResponse::new(body.clone(), SystemTime::now()))
+            .await
+            .unwrap();
+
+        assert_eq!(store.get(KEY).await.unwrap().unwrap().body, body);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ignores_other_files() {
+        let dir = store_dir("other");
+        fs::create_dir_all(&dir).unwrap();
+        let others = ["notes.json", "other.r6stats", "0123456789abcdef.json"];
+        for other in &others {
+            fs::write(dir.join(other), b"{}").unwrap();
+        }
+
+        let store = DiskStore::new(&dir).max_entries(1);
+        store.insert(KEY, response()).await.unwrap();
+        store.insert("other", response()).await.unwrap();
+
+        assert_eq!(store.len().await, 1);
+        assert!(others.iter().all(|other| dir.join(other).exists()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_entries() {
+        let dir = store_dir("max");
+        let store = DiskStore::new(&dir).max_entries(2);
+
+        store.insert("a", response()).await.unwrap();
+        store.insert("b", response()).await.unwrap();
+        let age = |key, secs| {
+            fs::File::options()
+                .write(true)
+                .open(store.path(key))
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(secs))
+                .unwrap()
+        };
+        age("a", 300);
+        age("b", 200);
+
+        // Reading "a" makes "b" the least recently used
+        assert!(store.get("a").await.unwrap().is_some());
+        store.insert("c", response()).await.unwrap();
+
+        assert!(store.get("a").await.unwrap().is_some());
+        assert!(store.get("b").await.unwrap().is_none());
+        assert!(store.get("c").await.unwrap().is_some());
+        assert_eq!(store.len().await, 2);
+        assert_eq!(store.evictions().await, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
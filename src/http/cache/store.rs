@@ -0,0 +1,219 @@
+//! Module for the stores holding cached responses.
+
+use crate::http::BoxFuture;
+use crate::Error;
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+/// A cached response body.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CachedResponse {
+    /// The raw response body.
+    pub body: Bytes,
+    /// When the response was received, or last revalidated.
+    pub fetched_at: SystemTime,
+    /// The `ETag` header of the response.
+    pub etag: Option<String>,
+    /// The `Last-Modified` header of the response.
+    pub last_modified: Option<String>,
+}
+
+impl CachedResponse {
+    /// Returns a response without validators received at `fetched_at`.
+    pub fn new(body: Bytes, fetched_at: SystemTime) -> Self {
+        Self {
+            body,
+            fetched_at,
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Returns true if the response can be revalidated with a conditional request.
+    pub(crate) fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+
+    /// Returns the response, confirmed by the server to be up to date.
+    pub(crate) fn revalidated(&self) -> Self {
+        Self {
+            fetched_at: SystemTime::now(),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn is_fresh(&self, ttl: Duration) -> bool {
+        // A fetch time in the future means the clock was changed, so the age is unknown
+        self.fetched_at.elapsed().is_ok_and(|age| age < ttl)
+    }
+}
+
+/// Holds cached responses by route.
+///
+/// The cache decides whether a stored response is still fresh, so stores return responses
+/// regardless of their age. Errors returned by a store are treated as cache misses, they never
+/// fail a request.
+///
+/// Two stores are provided:
+/// - [`MemoryStore`] keeping the most recently used responses in memory. This is the default.
+/// - [`DiskStore`] keeping responses on disk, so they survive restarts.
+///
+/// [`MemoryStore`]: struct.MemoryStore.html
+/// [`DiskStore`]: struct.DiskStore.html
+pub trait CacheStore: Debug + Send + Sync {
+    /// Returns the response stored for the route.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<CachedResponse>, Error>>;
+
+    /// Stores the response for the route, replacing any previous one.
+    fn insert<'a>(
+        &'a self,
+        key: &'a str,
+        response: CachedResponse,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// The number of stored responses.
+    fn len(&self) -> BoxFuture<'_, usize>;
+
+    /// Returns true if no responses are stored.
+    fn is_empty(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async move { self.len().await == 0 })
+    }
+
+    /// The number of responses evicted to make room for newer ones.
+    fn evictions(&self) -> BoxFuture<'_, u64> {
+        Box::pin(async { 0 })
+    }
+}
+
+/// Keeps the most recently used responses in memory.
+///
+/// When full, the least recently used response is evicted.
+#[derive(Debug)]
+pub struct MemoryStore {
+    inner: Mutex<Lru>,
+}
+
+impl MemoryStore {
+    /// Returns a store holding up to `capacity` responses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Lru {
+                capacity,
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+                evictions: 0,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<CachedResponse>, Error>> {
+        let cached = self.lock().get(key);
+        Box::pin(async { Ok(cached) })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        key: &'a str,
+        response: CachedResponse,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.lock().insert(key, response);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn len(&self) -> BoxFuture<'_, usize> {
+        let len = self.lock().entries.len();
+        Box::pin(async move { len })
+    }
+
+    fn evictions(&self) -> BoxFuture<'_, u64> {
+        let evictions = self.lock().evictions;
+        Box::pin(async move { evictions })
+    }
+}
+
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    /// The responses, along with the tick they were last used at.
+    entries: HashMap<String, (CachedResponse, u64)>,
+    /// The keys by the tick they were last used at, least recently used first.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    evictions: u64,
+}
+
+impl Lru {
+    fn get(&mut self, key: &str) -> Option<CachedResponse> {
+        let tick = self.next_tick();
+        let (cached, used_at) = self.entries.get_mut(key)?;
+
+        let key = self.recency.remove(used_at).unwrap();
+        self.recency.insert(tick, key);
+        *used_at = tick;
+
+        Some(cached.clone())
+    }
+
+    fn insert(&mut self, key: &str, cached: CachedResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let tick = self.next_tick();
+        if let Some((_, used_at)) = self.entries.insert(key.to_string(), (cached, tick)) {
+            self.recency.remove(&used_at);
+        }
+        self.recency.insert(tick, key.to_string());
+
+        while self.entries.len() > self.capacity {
+            let (_, oldest) = self.recency.pop_first().unwrap();
+            self.entries.remove(&oldest);
+            self.evictions += 1;
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStore, CachedResponse, MemoryStore};
+    use bytes::Bytes;
+    use std::time::SystemTime;
+
+    fn body(s: &'static str) -> CachedResponse {
+        CachedResponse::new(Bytes::from_static(s.as_bytes()), SystemTime::now())
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        let store = MemoryStore::new(2);
+
+        store.insert("a", body("a")).await.unwrap();
+        store.insert("b", body("b")).await.unwrap();
+
+        // `a` is now used more recently than `b`
+        assert!(store.get("a").await.unwrap().is_some());
+        store.insert("c", body("c")).await.unwrap();
+
+        assert!(store.get("a").await.unwrap().is_some());
+        assert!(store.get("b").await.unwrap().is_none());
+        assert!(store.get("c").await.unwrap().is_some());
+        assert_eq!(store.evictions().await, 1);
+        assert_eq!(store.len().await, 2);
+    }
+}
@@ -1,14 +1,24 @@
 use crate::Error as CrateError;
+use bytes::Bytes;
 use reqwest::{Error as ReqwestError, StatusCode};
+use serde::de::Error as _;
+use serde::Deserialize;
+use serde_json::Error as JsonError;
 use std::error::Error as StdError;
 use std::fmt::{self, Display, Formatter};
+use std::io::Error as IoError;
+use std::sync::Arc;
+use std::time::Duration;
 use url::ParseError;
 
 /// Errors related to HTTP.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Error {
     url: Option<String>,
     kind: Kind,
+    retry_after: Option<Duration>,
+    body: Option<Bytes>,
+    api_error: Option<Box<ApiError>>,
 }
 
 impl Error {
@@ -17,6 +27,11 @@ impl Error {
         self.url.clone()
     }
 
+    /// Returns the delay requested by the server's `Retry-After` header, if sent.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
     /// Returns the status code, if the error was generated from a response.
     pub fn unsuccessful_request(&self) -> Option<StatusCode> {
         if let Kind::UnsuccessfulRequest(status) = self.kind {
@@ -26,6 +41,18 @@ impl Error {
         }
     }
 
+    /// Returns the body of the unsuccessful response, if one was sent.
+    ///
+    /// Bodies are truncated to 16 KiB.
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    /// Returns the error the API reported in the body of the unsuccessful response, if any.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        self.api_error.as_deref()
+    }
+
     /// Returns the parse error, if the error was generated from URL parsing.
     pub fn url_error(&self) -> Option<ParseError> {
         if let Kind::UrlError(err) = self.kind {
@@ -49,7 +76,10 @@ impl From<ReqwestError> for Error {
     fn from(e: ReqwestError) -> Self {
         Self {
             url: e.url().map(|u| u.as_str().to_string()),
-            kind: Kind::RequestError(e),
+            kind: Kind::RequestError(Arc::new(e)),
+            retry_after: None,
+            body: None,
+            api_error: None,
         }
     }
 }
@@ -59,22 +89,48 @@ impl StdError for Error {
         match self.kind {
             Kind::UnsuccessfulRequest(_) => None,
             Kind::UrlError(ref err) => Some(err),
-            Kind::RequestError(ref err) => Some(err),
+            Kind::RequestError(ref err) => Some(&**err),
         }
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        fmt::Display::fmt(&self.kind, f)
+        fmt::Display::fmt(&self.kind, f)?;
+
+        if let Some(api_error) = &self.api_error {
+            write!(f, " ({})", api_error.message)?;
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Debug)]
+/// Deserialized error body of an unsuccessful response.
+#[derive(Deserialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct ApiError {
+    /// The status reported by the API, usually `"error"`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// The error message, like `"Invalid platform"`.
+    #[serde(alias = "error")]
+    pub message: String,
+}
+
+impl ApiError {
+    /// Parses the body of an unsuccessful response, returning `None` if it isn't an API error.
+    pub(crate) fn from_body(body: &[u8]) -> Option<Box<Self>> {
+        serde_json::from_slice(body).ok()
+    }
+}
+
+#[derive(Clone, Debug)]
 enum Kind {
     UnsuccessfulRequest(StatusCode),
     UrlError(ParseError),
-    RequestError(ReqwestError),
+    // Shared with the callers of a coalesced request, since it can't be cloned
+    RequestError(Arc<ReqwestError>),
 }
 
 impl Display for Kind {
@@ -87,23 +143,104 @@ impl Display for Kind {
     }
 }
 
-pub(crate) fn unsuccessful_request(url: &str, status: StatusCode) -> CrateError {
-    CrateError::HttpError(Error {
+/// Returns the error for an unsuccessful response.
+///
+/// Statuses with a dedicated variant in the crate [`Error`] are mapped to it. Either way the
+/// error keeps the URL and the response `body`.
+///
+/// [`Error`]: ../../error/enum.Error.html
+pub(crate) fn unsuccessful_request(
+    url: &str,
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    body: Bytes,
+) -> CrateError {
+    let source = Error {
         url: Some(url.to_string()),
         kind: Kind::UnsuccessfulRequest(status),
-    })
+        retry_after,
+        api_error: ApiError::from_body(&body),
+        body: Some(body).filter(|b| !b.is_empty()),
+    };
+    let message = source.api_error().map(|e| e.message.clone());
+
+    match status {
+        StatusCode::UNAUTHORIZED => CrateError::Unauthorized {
+            message,
+            source: Box::new(source),
+        },
+        StatusCode::TOO_MANY_REQUESTS => CrateError::RateLimited {
+            retry_after,
+            message,
+            source: Box::new(source),
+        },
+        status if status.is_server_error() => CrateError::ServerError {
+            status,
+            message,
+            source: Box::new(source),
+        },
+        _ => CrateError::HttpError(source),
+    }
 }
 
 pub(crate) fn url_error(url: &str, e: ParseError) -> CrateError {
     CrateError::HttpError(Error {
         url: Some(url.to_string()),
         kind: Kind::UrlError(e),
+        retry_after: None,
+        body: None,
+        api_error: None,
     })
 }
 
 pub(crate) fn request_error(e: ReqwestError) -> CrateError {
-    CrateError::HttpError(Error {
-        url: e.url().map(|u| u.as_str().to_string()),
-        kind: Kind::RequestError(e),
-    })
+    CrateError::HttpError(Error::from(e))
+}
+
+/// Returns a copy of the error to hand out to every caller sharing a request.
+///
+/// Errors which can't be copied, like IO errors, are copied with their kind and message only.
+pub(crate) fn share(err: &CrateError) -> CrateError {
+    match err {
+        CrateError::PlayerNotFound {
+            username,
+            platform,
+            message,
+            source,
+        } => CrateError::PlayerNotFound {
+            username: username.clone(),
+            platform: *platform,
+            message: message.clone(),
+            source: source.clone(),
+        },
+        CrateError::Unauthorized { message, source } => CrateError::Unauthorized {
+            message: message.clone(),
+            source: source.clone(),
+        },
+        CrateError::RateLimited {
+            retry_after,
+            message,
+            source,
+        } => CrateError::RateLimited {
+            retry_after: *retry_after,
+            message: message.clone(),
+            source: source.clone(),
+        },
+        CrateError::ServerError {
+            status,
+            message,
+            source,
+        } => CrateError::ServerError {
+            status: *status,
+            message: message.clone(),
+            source: source.clone(),
+        },
+        CrateError::HttpError(err) => CrateError::HttpError(err.clone()),
+        CrateError::JsonError(err) => CrateError::JsonError(JsonError::custom(err)),
+        CrateError::UsernameMalformed => CrateError::UsernameMalformed,
+        CrateError::QueryMalformed => CrateError::QueryMalformed,
+        CrateError::UnknownUbisoftId(id) => CrateError::UnknownUbisoftId(id.clone()),
+        CrateError::IoError(err) => CrateError::IoError(IoError::new(err.kind(), err.to_string())),
+        CrateError::Other(s) => CrateError::Other(s.clone()),
+    }
 }
@@ -0,0 +1,161 @@
+//! Module for parsing the ratelimit headers sent by the server.
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const LIMIT: &str = "x-ratelimit-limit";
+const REMAINING: &str = "x-ratelimit-remaining";
+const RESET: &str = "x-ratelimit-reset";
+const RETRY_AFTER: &str = "retry-after";
+
+// Reset values above this are unix timestamps, values below are seconds from now.
+const RESET_TIMESTAMP_THRESHOLD: f64 = 1_000_000_000.0;
+
+// Delays reported by the server are capped at this, so a bogus header can't block requests
+// indefinitely.
+const MAX_DELAY: Duration = Duration::from_secs(15 * 60);
+
+/// Ratelimit information reported by the server.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct RatelimitHeaders {
+    /// `X-RateLimit-Limit`
+    pub limit: Option<u16>,
+    /// `X-RateLimit-Remaining`
+    pub remaining: Option<u16>,
+    /// `X-RateLimit-Reset`, relative to now
+    pub reset_in: Option<Duration>,
+    /// `Retry-After`, relative to now
+    pub retry_after: Option<Duration>,
+}
+
+impl RatelimitHeaders {
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            limit: header_str(headers, LIMIT).and_then(|v| v.parse().ok()),
+            remaining: header_str(headers, REMAINING).and_then(|v| v.parse().ok()),
+            reset_in: header_str(headers, RESET).and_then(parse_reset),
+            retry_after: header_str(headers, RETRY_AFTER).and_then(parse_retry_after),
+        }
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+}
+
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v >= 0.0)?;
+
+    // Values too large for a `Duration` are far in the future either way
+    let value = Duration::try_from_secs_f64(value).unwrap_or(Duration::MAX);
+
+    if value.as_secs_f64() < RESET_TIMESTAMP_THRESHOLD {
+        return Some(value.min(MAX_DELAY));
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(value.checked_sub(now).unwrap_or_default().min(MAX_DELAY))
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs).min(MAX_DELAY));
+    }
+
+    // Otherwise an HTTP date, e.g. `Wed, 21 Oct 2015 07:28:00 GMT`
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&Utc) - Utc::now();
+    Some(delay.to_std().unwrap_or_default().min(MAX_DELAY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RatelimitHeaders, MAX_DELAY};
+    use chrono::{Duration as ChronoDuration, Utc};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let map = headers(&[
+            ("x-ratelimit-limit", "60".to_string()),
+            ("x-ratelimit-remaining", "42".to_string()),
+            ("x-ratelimit-reset", "30".to_string()),
+            ("retry-after", "5".to_string()),
+        ]);
+
+        let parsed = RatelimitHeaders::from_headers(&map);
+
+        assert_eq!(parsed.limit, Some(60));
+        assert_eq!(parsed.remaining, Some(42));
+        assert_eq!(parsed.reset_in, Some(Duration::from_secs(30)));
+        assert_eq!(parsed.retry_after, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_parse_reset_timestamp() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let reset = now.as_secs() + 20;
+        let map = headers(&[("x-ratelimit-reset", reset.to_string())]);
+
+        let reset_in = RatelimitHeaders::from_headers(&map).reset_in.unwrap();
+
+        assert!(reset_in <= Duration::from_secs(20));
+        assert!(reset_in >= Duration::from_secs(18));
+    }
+
+    #[test]
+    fn test_parse_retry_after_date() {
+        let date = Utc::now() + ChronoDuration::seconds(10);
+        let map = headers(&[("retry-after", date.to_rfc2822())]);
+
+        let retry_after = RatelimitHeaders::from_headers(&map).retry_after.unwrap();
+
+        assert!(retry_after <= Duration::from_secs(10));
+        assert!(retry_after >= Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_parse_huge_values() {
+        let map = headers(&[
+            ("x-ratelimit-reset", "1e20".to_string()),
+            ("retry-after", u64::MAX.to_string()),
+        ]);
+
+        let parsed = RatelimitHeaders::from_headers(&map);
+
+        assert_eq!(parsed.reset_in, Some(MAX_DELAY));
+        assert_eq!(parsed.retry_after, Some(MAX_DELAY));
+    }
+
+    #[test]
+    fn test_parse_invalid_headers() {
+        let map = headers(&[
+            ("x-ratelimit-limit", "many".to_string()),
+            ("x-ratelimit-remaining", "-1".to_string()),
+            ("x-ratelimit-reset", "soon".to_string()),
+            ("retry-after", "later".to_string()),
+        ]);
+
+        assert_eq!(
+            RatelimitHeaders::from_headers(&map),
+            RatelimitHeaders::default()
+        );
+    }
+}
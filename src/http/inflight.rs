@@ -0,0 +1,109 @@
+//! Module for sharing the responses of concurrent identical requests.
+
+use super::error::share;
+use super::{Priority, Request};
+use crate::Error;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tokio::sync::oneshot;
+
+/// The outcome handed to the callers waiting for a request.
+pub(crate) type Outcome = Result<Bytes, Error>;
+
+/// Identifies the requests which can share a response.
+///
+/// Requests skipping the cache or sent with another [`Priority`] aren't shared, so callers
+/// neither get a response they meant to bypass nor wait in the queue of another priority.
+///
+/// [`Priority`]: ../enum.Priority.html
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    url: String,
+    priority: Priority,
+    force_refresh: bool,
+}
+
+impl Key {
+    pub fn of(request: &Request) -> Self {
+        Self {
+            url: request.url.as_str().to_string(),
+            priority: request.priority,
+            force_refresh: request.force_refresh,
+        }
+    }
+}
+
+/// Keeps track of the requests currently in flight.
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    requests: Mutex<HashMap<Key, Vec<oneshot::Sender<Outcome>>>>,
+}
+
+/// The role of a caller in a request.
+pub(crate) enum Joined<'a> {
+    /// No request to the route is in flight, so the caller sends it.
+    Leader(Leader<'a>),
+    /// The caller waits for the request in flight.
+    Follower(oneshot::Receiver<Outcome>),
+}
+
+impl InFlight {
+    /// Joins the request in flight, or starts a new one.
+    pub fn join(&self, key: Key) -> Joined<'_> {
+        let mut requests = self.lock();
+
+        match requests.get_mut(&key) {
+            Some(waiting) => {
+                let (tx, rx) = oneshot::channel();
+                waiting.push(tx);
+                Joined::Follower(rx)
+            }
+            None => {
+                requests.insert(key.clone(), Vec::new());
+                Joined::Leader(Leader {
+                    in_flight: self,
+                    key: Some(key),
+                })
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Key, Vec<oneshot::Sender<Outcome>>>> {
+        self.requests.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The caller sending a request on behalf of all callers waiting for it.
+///
+/// If dropped before completing, e.g. because the caller was cancelled, the waiting callers
+/// are woken up to send the request themselves.
+pub(crate) struct Leader<'a> {
+    in_flight: &'a InFlight,
+    key: Option<Key>,
+}
+
+impl Leader<'_> {
+    /// Hands the result of the request to all waiting callers.
+    pub fn complete(mut self, result: &Result<Bytes, Error>) {
+        let key = self.key.take().unwrap();
+        let waiting = self.in_flight.lock().remove(&key).unwrap_or_default();
+
+        for tx in waiting {
+            let outcome = match result {
+                Ok(body) => Ok(body.clone()),
+                Err(err) => Err(share(err)),
+            };
+            let _ = tx.send(outcome);
+        }
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            // Dropping the senders wakes up the waiting callers
+            self.in_flight.lock().remove(&key);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Module for the priority of requests.
+
+/// The priority of a request while waiting for the ratelimit.
+///
+/// Free slots are handed to waiting requests with a higher priority first. Requests of the same
+/// priority are sent in the order they were made. The request which is next in line keeps its
+/// place when a request with a higher priority comes in.
+///
+/// A waiting request is raised to the next higher priority after the [`priority_aging`]
+/// interval, so requests with a low priority are sent eventually. Has no effect if ratelimiting
+/// is disabled.
+///
+/// [`priority_aging`]: struct.RatelimitBuilder.html#method.priority_aging
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// E.g. background refreshes.
+    Low,
+    /// The priority of requests unless stated otherwise.
+    #[default]
+    Normal,
+    /// E.g. requests a user is waiting for.
+    High,
+}
+
+#[cfg(feature = "ratelimiting")]
+impl Priority {
+    pub(crate) fn level(self) -> usize {
+        self as usize
+    }
+}
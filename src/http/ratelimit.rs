@@ -1,10 +1,24 @@
 //! Module for ratelimiting requests before sending them.
 
-use crate::Error;
+mod backend;
+mod file;
+mod queue;
+mod strategy;
+
+pub use self::backend::{RatelimitBackend, SharedRatelimit};
+pub use self::file::FileRatelimit;
+pub use self::strategy::Strategy;
+
+use self::queue::Queue;
+use self::strategy::Window;
+use super::headers::RatelimitHeaders;
+use super::Priority;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 use tokio::time::{delay_for, Duration, Instant};
 
 const DEFAULT_RATE_LIMIT: u16 = 60;
 const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_PRIORITY_AGING: Duration = Duration::from_secs(30);
 
 /// The builder for [`Ratelimit`].
 ///
@@ -13,6 +27,8 @@ const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
 pub struct RatelimitBuilder {
     limit: Option<u16>,
     interval: Option<Duration>,
+    strategy: Option<Strategy>,
+    priority_aging: Option<Duration>,
 }
 
 impl RatelimitBuilder {
@@ -41,31 +57,65 @@ impl RatelimitBuilder {
         self
     }
 
+    /// Sets the [`Strategy`] of the [`Ratelimit`].
+    ///
+    /// Defaults to [`Strategy::FixedWindow`].
+    ///
+    /// [`Strategy`]: enum.Strategy.html
+    /// [`Strategy::FixedWindow`]: enum.Strategy.html#variant.FixedWindow
+    /// [`Ratelimit`]: struct.Ratelimit.html
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Sets the time after which a waiting request is treated like one with the next higher
+    /// [`Priority`].
+    ///
+    /// Keeps requests with a low priority from waiting forever while there are requests with a
+    /// higher priority. Defaults to 30 seconds.
+    ///
+    /// [`Priority`]: enum.Priority.html
+    pub fn priority_aging(mut self, aging: Duration) -> Self {
+        self.priority_aging = Some(aging);
+        self
+    }
+
     /// Returns the finished [`Ratelimit`].
     ///
     /// [`Ratelimit`]: struct.Ratelimit.html
     pub(crate) fn build(self) -> Ratelimit {
         let limit = self.limit.unwrap_or(DEFAULT_RATE_LIMIT);
+        let strategy = self.strategy.unwrap_or_default();
         Ratelimit {
             limit,
-            remaining: limit,
             interval: self.interval.unwrap_or(DEFAULT_INTERVAL),
-            reset_at: Instant::now(),
+            strategy,
+            window: Window::new(strategy, limit, Instant::now()),
+            blocked_until: None,
+            priority_aging: self.priority_aging.unwrap_or(DEFAULT_PRIORITY_AGING),
         }
     }
 }
 
 /// Handles ratelimiting.
 ///
-/// When a request exceeds the limit, it will be delayed until next [`reset`].
+/// Lets [`limit`] requests through per [`interval`], spread over time according to the
+/// [`Strategy`]. When a request exceeds the limit, it will be delayed until a slot is available
+/// again. Delayed requests are sent in the order they were made.
 ///
-/// [`reset`]: struct.Ratelimit.html#method.reset_at
-#[derive(Copy, Clone, Debug)]
+/// [`limit`]: struct.Ratelimit.html#method.limit
+/// [`interval`]: struct.Ratelimit.html#method.interval
+/// [`Strategy`]: enum.Strategy.html
+#[derive(Clone, Debug)]
 pub struct Ratelimit {
     limit: u16,
-    remaining: u16,
     interval: Duration,
-    reset_at: Instant,
+    strategy: Strategy,
+    window: Window,
+    /// Set when the server asked to back off.
+    blocked_until: Option<Instant>,
+    priority_aging: Duration,
 }
 
 impl Ratelimit {
@@ -78,9 +128,14 @@ impl Ratelimit {
         self.limit
     }
 
-    /// The number of requests remaining in the period of time.
+    /// The number of requests which can be sent right away.
     pub fn remaining(&self) -> u16 {
-        self.remaining
+        let now = Instant::now();
+        if self.blocked_until.is_some_and(|until| until > now) {
+            return 0;
+        }
+
+        self.window.remaining(self.limit, self.interval, now)
     }
 
     /// The ratelimit interval.
@@ -88,46 +143,98 @@ impl Ratelimit {
         self.interval
     }
 
+    /// The way requests are spread over the interval.
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// The time after which a waiting request is treated like one with the next higher
+    /// [`Priority`].
+    ///
+    /// [`Priority`]: enum.Priority.html
+    pub fn priority_aging(&self) -> Duration {
+        self.priority_aging
+    }
+
     /// The absolute time at which the ratelimit resets.
+    ///
+    /// For the [`SlidingLog`] and [`TokenBucket`] strategies, this is the time at which all
+    /// slots are available again.
+    ///
+    /// [`SlidingLog`]: enum.Strategy.html#variant.SlidingLog
+    /// [`TokenBucket`]: enum.Strategy.html#variant.TokenBucket
     pub fn reset_at(&self) -> Instant {
-        self.reset_at
+        let reset_at = self.window.reset_at(self.interval, Instant::now());
+        self.blocked_until
+            .map_or(reset_at, |until| until.max(reset_at))
     }
 
     /// The duration from now in which the ratelimit resets.
     ///
     /// Returns `None` if the reset time is in the past.
     pub fn reset_in(&self) -> Option<Duration> {
-        self.reset_at.checked_duration_since(Instant::now())
+        self.reset_at().checked_duration_since(Instant::now())
     }
 
-    pub(super) async fn pre_hook(&mut self) -> Result<(), Error> {
+    /// Takes a slot for a request.
+    ///
+    /// Returns the duration until the next slot is available if no slot is left.
+    fn take_slot(&mut self) -> Result<(), Duration> {
         if self.limit == 0 {
             return Ok(());
         }
 
-        let delay = match self.reset_in() {
-            Some(delay) => delay,
-            None => {
-                self.reset();
-                self.remaining -= 1;
-
-                return Ok(());
+        let now = Instant::now();
+        if let Some(until) = self.blocked_until {
+            if until > now {
+                return Err(until - now);
             }
-        };
+            self.blocked_until = None;
+        }
 
-        if self.remaining == 0 {
-            delay_for(delay).await;
-            return Ok(());
+        self.window.take(self.limit, self.interval, now)
+    }
+
+    /// Syncs the ratelimit with the values reported by the server.
+    ///
+    /// Has no effect if ratelimiting is disabled.
+    pub(super) fn update(&mut self, headers: &RatelimitHeaders) {
+        if self.limit == 0 {
+            return;
         }
 
-        self.remaining -= 1;
+        let now = Instant::now();
 
-        Ok(())
+        if let Some(limit) = headers.limit.filter(|&l| l > 0) {
+            self.limit = limit;
+        }
+
+        if let Some(reset_at) = headers.reset_in.and_then(|d| now.checked_add(d)) {
+            self.window.set_reset_at(reset_at);
+        }
+
+        if let Some(remaining) = headers.remaining {
+            self.window
+                .set_remaining(remaining, self.limit, self.interval, now);
+        }
+
+        if let Some(blocked_until) = headers.retry_after.and_then(|d| now.checked_add(d)) {
+            self.blocked_until = Some(blocked_until);
+        }
     }
 
-    fn reset(&mut self) {
-        self.reset_at = Instant::now() + self.interval;
-        self.remaining = self.limit;
+    /// Uses up all slots, e.g. after the server responded with HTTP 429.
+    ///
+    /// Returns `false` if ratelimiting is disabled.
+    pub(super) fn exhaust(&mut self) -> bool {
+        if self.limit == 0 {
+            return false;
+        }
+
+        self.window
+            .exhaust(self.limit, self.interval, Instant::now());
+
+        true
     }
 }
 
@@ -137,13 +244,123 @@ impl Default for Ratelimit {
     }
 }
 
+/// Shares a [`Ratelimit`] between the requests of a client.
+///
+/// [`Ratelimit`]: struct.Ratelimit.html
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    ratelimit: Mutex<Ratelimit>,
+    queue: Queue,
+}
+
+impl Limiter {
+    pub fn new(ratelimit: Ratelimit) -> Self {
+        Self {
+            queue: Queue::new(ratelimit.priority_aging),
+            ratelimit: Mutex::new(ratelimit),
+        }
+    }
+
+    /// Returns a copy of the current state.
+    pub fn ratelimit(&self) -> Ratelimit {
+        self.lock().clone()
+    }
+
+    /// Waits until the request may be sent.
+    ///
+    /// Dropping the future before it completes gives up the place in the queue without using
+    /// up a slot.
+    pub async fn acquire(&self, priority: Priority) {
+        if self.lock().limit == 0 {
+            return;
+        }
+
+        let _turn = self.queue.enter(priority).await;
+
+        loop {
+            let result = self.lock().take_slot();
+            match result {
+                Ok(()) => return,
+                Err(delay) => delay_for(delay).await,
+            }
+        }
+    }
+
+    /// See [`Ratelimit::update`].
+    ///
+    /// [`Ratelimit::update`]: struct.Ratelimit.html#method.update
+    pub fn update(&self, headers: &RatelimitHeaders) {
+        self.lock().update(headers);
+    }
+
+    /// See [`Ratelimit::exhaust`].
+    ///
+    /// [`Ratelimit::exhaust`]: struct.Ratelimit.html#method.exhaust
+    pub fn exhaust(&self) -> bool {
+        self.lock().exhaust()
+    }
+
+    // Every update of the state is a few plain assignments, so it stays consistent even if
+    // another thread panicked while holding the lock.
+    fn lock(&self) -> MutexGuard<'_, Ratelimit> {
+        self.ratelimit
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::http::{Ratelimit, RatelimitBuilder};
-    use tokio::time::{Duration, Instant};
+    use super::Limiter;
+    use crate::http::headers::RatelimitHeaders;
+    use crate::http::{Priority, Ratelimit, RatelimitBuilder, Strategy};
+    use std::sync::{Arc, Mutex};
+    use tokio::task::yield_now;
+    use tokio::time::{timeout, Duration, Instant};
 
     const NO_LIMIT_TEST_CAP: usize = 100;
 
+    fn limiter(limit: u16, interval: u64) -> Arc<Limiter> {
+        limiter_with(Strategy::FixedWindow, limit, interval)
+    }
+
+    fn limiter_with(strategy: Strategy, limit: u16, interval: u64) -> Arc<Limiter> {
+        let ratelimit = RatelimitBuilder::new()
+            .limit(limit)
+            .interval(Duration::from_secs(interval))
+            .strategy(strategy)
+            .build();
+
+        Arc::new(Limiter::new(ratelimit))
+    }
+
+    /// Makes `count` requests one after another and returns the order in which they were let
+    /// through, along with the second they were let through at.
+    async fn run_requests(limiter: &Arc<Limiter>, count: usize) -> Vec<(usize, u64)> {
+        let start = Instant::now();
+        let passed = Arc::new(Mutex::new(Vec::new()));
+
+        let mut handles = Vec::new();
+        for i in 0..count {
+            let limiter = limiter.clone();
+            let passed = passed.clone();
+            handles.push(tokio::spawn(async move {
+                limiter.acquire(Priority::Normal).await;
+                let at = Instant::now().duration_since(start).as_secs();
+                passed.lock().unwrap().push((i, at));
+            }));
+            // Let the request enter the queue before making the next one
+            let () = yield_now().await;
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let passed = passed.lock().unwrap().clone();
+        passed
+    }
+
     #[tokio::test]
     async fn test_default_ratelimit() {
         let r = Ratelimit::default();
@@ -156,49 +373,254 @@ mod tests {
 
     #[tokio::test]
     async fn test_ratelimiting() {
-        // Limit of 3 requests in 3 seconds
+        tokio::time::pause();
+
+        // Limit of 3 requests in 10 seconds
+        let limiter = limiter(3, 10);
+
+        let passed = run_requests(&limiter, 7).await;
+
+        let expected = vec![(0, 0), (1, 0), (2, 0), (3, 10), (4, 10), (5, 10), (6, 20)];
+        assert_eq!(passed, expected);
+    }
+
+    #[tokio::test]
+    async fn test_delayed_request_starts_interval() {
+        tokio::time::pause();
+
+        let limiter = limiter(3, 10);
+        run_requests(&limiter, 4).await;
+
+        // The delayed request used up a slot of the new interval
+        let r = limiter.ratelimit();
+        assert_eq!(r.remaining(), 2);
+        assert_eq!(r.reset_in(), Some(Duration::from_secs(10)));
+    }
+
+    #[tokio::test]
+    async fn test_interval_starts_with_request() {
+        tokio::time::pause();
+
+        let limiter = limiter(2, 10);
+        run_requests(&limiter, 1).await;
+
+        // Idle for longer than the interval
+        tokio::time::delay_for(Duration::from_secs(25)).await;
+
+        let passed = run_requests(&limiter, 3).await;
+        assert_eq!(passed, vec![(0, 0), (1, 0), (2, 10)]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request() {
+        tokio::time::pause();
+
+        let limiter = limiter(1, 10);
+        limiter.acquire(Priority::Normal).await;
+
+        // Gives up while waiting for the reset
+        let cancelled = timeout(Duration::from_secs(1), limiter.acquire(Priority::Normal)).await;
+        assert!(cancelled.is_err());
+
+        // The next request takes over the place in the queue and the slot
+        let passed = run_requests(&limiter, 2).await;
+        assert_eq!(passed, vec![(0, 9), (1, 19)]);
+    }
+
+    /// One request, then five more just before the end of the first interval.
+    async fn run_boundary_requests(limiter: &Arc<Limiter>) -> Vec<(usize, u64)> {
+        run_requests(limiter, 1).await;
+        tokio::time::delay_for(Duration::from_secs(9)).await;
+        run_requests(limiter, 5).await
+    }
+
+    #[tokio::test]
+    async fn test_fixed_window_boundary() {
+        tokio::time::pause();
+
+        let limiter = limiter(3, 10);
+        let passed = run_boundary_requests(&limiter).await;
+
+        // 5 requests within a second, as the second interval starts right after the first
+        let expected = vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 1)];
+        assert_eq!(passed, expected);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_log() {
+        tokio::time::pause();
+
+        let limiter = limiter_with(Strategy::SlidingLog, 3, 10);
+        let passed = run_boundary_requests(&limiter).await;
+
+        // A slot frees up 10 seconds after the request which used it
+        let expected = vec![(0, 0), (1, 0), (2, 1), (3, 10), (4, 10)];
+        assert_eq!(passed, expected);
+
+        let r = limiter.ratelimit();
+        assert_eq!(r.remaining(), 0);
+        assert_eq!(r.reset_in(), Some(Duration::from_secs(10)));
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket() {
+        tokio::time::pause();
+
+        // A token every 5 seconds, up to 4 at once
+        let limiter = limiter_with(Strategy::TokenBucket { burst: 4 }, 2, 10);
+
+        let expected = vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 5), (5, 10)];
+        assert_eq!(run_requests(&limiter, 6).await, expected);
+
+        // The bucket doesn't fill up beyond the burst while idle
+        tokio::time::delay_for(Duration::from_secs(60)).await;
+        assert_eq!(limiter.ratelimit().remaining(), 4);
+        assert_eq!(run_requests(&limiter, 6).await, expected);
+    }
+
+    #[tokio::test]
+    async fn test_update_strategies() {
+        tokio::time::pause();
+
+        for &strategy in [Strategy::SlidingLog, Strategy::TokenBucket { burst: 10 }].iter() {
+            let mut r = RatelimitBuilder::new()
+                .limit(10)
+                .interval(Duration::from_secs(10))
+                .strategy(strategy)
+                .build();
+
+            r.update(&RatelimitHeaders {
+                remaining: Some(2),
+                ..RatelimitHeaders::default()
+            });
+            assert_eq!(r.remaining(), 2, "{:?}", strategy);
+
+            assert!(r.exhaust());
+            assert_eq!(r.remaining(), 0, "{:?}", strategy);
+
+            r.update(&RatelimitHeaders {
+                retry_after: Some(Duration::from_secs(30)),
+                ..RatelimitHeaders::default()
+            });
+            assert_eq!(
+                r.reset_in(),
+                Some(Duration::from_secs(30)),
+                "{:?}",
+                strategy
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priority() {
+        tokio::time::pause();
+
+        let limiter = limiter(1, 10);
+        limiter.acquire(Priority::Normal).await;
+
+        let start = Instant::now();
+        let passed = Arc::new(Mutex::new(Vec::new()));
+        let priorities = [Priority::Low, Priority::Low, Priority::Low, Priority::High];
+
+        let mut handles = Vec::new();
+        for (i, &priority) in priorities.iter().enumerate() {
+            let limiter = limiter.clone();
+            let passed = passed.clone();
+            handles.push(tokio::spawn(async move {
+                limiter.acquire(priority).await;
+                let at = Instant::now().duration_since(start).as_secs();
+                passed.lock().unwrap().push((i, at));
+            }));
+        }
+
+        // The tasks enter the queue in the order they were spawned
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // The first request was already waiting for the reset when the high priority one came
+        let expected = vec![(0, 10), (3, 20), (1, 30), (2, 40)];
+        assert_eq!(*passed.lock().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_exhaust() {
         let mut r = RatelimitBuilder::new()
             .limit(3)
             .interval(Duration::from_secs(3))
             .build();
 
-        // Initial reset
-        r.reset();
+        assert!(r.exhaust());
+        assert_eq!(r.remaining(), 0);
+        assert!(r.reset_in().is_some());
 
-        // Save reset_at to assert that following requests pass
-        let reset_at = r.reset_at();
+        let mut disabled = RatelimitBuilder::new().limit(0).build();
+        assert!(!disabled.exhaust());
+    }
 
-        for i in 0..r.limit() {
-            r.pre_hook().await.unwrap();
-            assert_eq!(r.remaining(), r.limit() - i - 1);
-        }
+    #[tokio::test]
+    async fn test_update() {
+        let mut r = RatelimitBuilder::new()
+            .limit(60)
+            .interval(Duration::from_secs(60))
+            .build();
 
-        // Check if wrong ratelimited
-        assert_eq!(reset_at, r.reset_at());
+        r.update(&RatelimitHeaders {
+            limit: Some(100),
+            remaining: Some(12),
+            reset_in: Some(Duration::from_secs(10)),
+            retry_after: None,
+        });
 
-        // Next request should get ratelimited
-        r.pre_hook().await.unwrap();
+        assert_eq!(r.limit(), 100);
+        assert_eq!(r.remaining(), 12);
+        assert!(r.reset_in().unwrap() <= Duration::from_secs(10));
 
-        assert!(Instant::now().checked_duration_since(reset_at).is_some());
+        r.update(&RatelimitHeaders {
+            retry_after: Some(Duration::from_secs(30)),
+            ..RatelimitHeaders::default()
+        });
 
-        r.pre_hook().await.unwrap();
+        assert_eq!(r.remaining(), 0);
+        assert!(r.reset_in().unwrap() > Duration::from_secs(10));
+    }
 
-        assert_eq!(r.remaining(), r.limit() - 1);
-        assert!(r.reset_in().is_some());
+    #[tokio::test]
+    async fn test_update_overflow() {
+        let mut r = RatelimitBuilder::new().limit(60).build();
+
+        r.update(&RatelimitHeaders {
+            reset_in: Some(Duration::MAX),
+            retry_after: Some(Duration::MAX),
+            ..RatelimitHeaders::default()
+        });
+
+        assert_eq!(r.limit(), 60);
     }
 
     #[tokio::test]
-    async fn test_disabled_ratelimiting() {
+    async fn test_update_disabled() {
         let mut r = RatelimitBuilder::new().limit(0).build();
 
-        r.reset();
+        r.update(&RatelimitHeaders {
+            limit: Some(60),
+            remaining: Some(60),
+            ..RatelimitHeaders::default()
+        });
 
-        let reset_at = r.reset_at();
+        assert_eq!(r.limit(), 0);
+    }
 
-        for _ in 0..NO_LIMIT_TEST_CAP {
-            r.pre_hook().await.unwrap();
-        }
+    #[tokio::test]
+    async fn test_disabled_ratelimiting() {
+        tokio::time::pause();
+
+        let limiter = limiter(0, 10);
+        let reset_at = limiter.ratelimit().reset_at();
+
+        let passed = run_requests(&limiter, NO_LIMIT_TEST_CAP).await;
 
-        assert_eq!(reset_at, r.reset_at());
+        assert!(passed.iter().all(|&(_, at)| at == 0));
+        assert_eq!(reset_at, limiter.ratelimit().reset_at());
     }
 }
@@ -0,0 +1,135 @@
+//! Module for the backends keeping track of the ratelimit.
+
+use super::{Limiter, Ratelimit, RatelimitBuilder};
+use crate::http::{BoxFuture, Priority, RatelimitHeaders};
+use crate::Error;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Keeps track of the ratelimit of one or more clients.
+///
+/// The client consults the backend before sending a request, and reports the ratelimit
+/// headers of every response back to it. Clients sharing a backend share the ratelimit.
+///
+/// Two backends are provided:
+/// - [`SharedRatelimit`] for clients within the same process. This is the default.
+/// - [`FileRatelimit`] for clients in several processes on the same host.
+///
+/// [`SharedRatelimit`]: struct.SharedRatelimit.html
+/// [`FileRatelimit`]: struct.FileRatelimit.html
+pub trait RatelimitBackend: Debug + Send + Sync {
+    /// Waits until a request may be sent, and takes a slot for it.
+    ///
+    /// Requests with a higher [`Priority`] should be let through first. Dropping the future
+    /// before it completes must not use up a slot.
+    ///
+    /// [`Priority`]: enum.Priority.html
+    fn acquire(&self, priority: Priority) -> BoxFuture<'_, Result<(), Error>>;
+
+    /// Syncs the ratelimit with the values reported by the server.
+    fn update(&self, headers: RatelimitHeaders) -> BoxFuture<'_, Result<(), Error>>;
+
+    /// Uses up all slots, after the server responded with HTTP 429.
+    ///
+    /// Returns `false` if ratelimiting is disabled.
+    fn exhaust(&self) -> BoxFuture<'_, Result<bool, Error>>;
+
+    /// Returns the current state of the ratelimit.
+    fn ratelimit(&self) -> BoxFuture<'_, Result<Ratelimit, Error>>;
+}
+
+/// A ratelimit shared between clients of the same process.
+///
+/// Clones share the same ratelimit, and requests of all clients are queued fairly.
+///
+/// ## Example
+/// ```rust
+/// use r6stats_client::http::SharedRatelimit;
+/// use r6stats_client::Client;
+///
+/// let ratelimit = SharedRatelimit::new(|r| r.limit(60));
+///
+/// let first = Client::builder("TOKEN")
+///     .ratelimit_backend(ratelimit.clone())
+///     .build()
+///     .unwrap();
+/// let second = Client::builder("TOKEN")
+///     .ratelimit_backend(ratelimit)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct SharedRatelimit {
+    limiter: Arc<Limiter>,
+}
+
+impl SharedRatelimit {
+    /// Returns a new ratelimit.
+    ///
+    /// # Args
+    ///
+    /// - `op` - Closure for building the [`Ratelimit`]
+    ///
+    /// [`Ratelimit`]: struct.Ratelimit.html
+    pub fn new<F>(op: F) -> Self
+    where
+        F: FnOnce(RatelimitBuilder) -> RatelimitBuilder,
+    {
+        Self::from_ratelimit(op(RatelimitBuilder::new()).build())
+    }
+
+    pub(crate) fn from_ratelimit(ratelimit: Ratelimit) -> Self {
+        Self {
+            limiter: Arc::new(Limiter::new(ratelimit)),
+        }
+    }
+}
+
+impl RatelimitBackend for SharedRatelimit {
+    fn acquire(&self, priority: Priority) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            self.limiter.acquire(priority).await;
+            Ok(())
+        })
+    }
+
+    fn update(&self, headers: RatelimitHeaders) -> BoxFuture<'_, Result<(), Error>> {
+        self.limiter.update(&headers);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn exhaust(&self) -> BoxFuture<'_, Result<bool, Error>> {
+        let exhausted = self.limiter.exhaust();
+        Box::pin(async move { Ok(exhausted) })
+    }
+
+    fn ratelimit(&self) -> BoxFuture<'_, Result<Ratelimit, Error>> {
+        let ratelimit = self.limiter.ratelimit();
+        Box::pin(async move { Ok(ratelimit) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RatelimitBackend, SharedRatelimit};
+    use crate::http::Priority;
+    use tokio::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_shared_between_clones() {
+        tokio::time::pause();
+
+        let first = SharedRatelimit::new(|r| r.limit(2).interval(Duration::from_secs(10)));
+        let second = first.clone();
+        let start = Instant::now();
+
+        first.acquire(Priority::Normal).await.unwrap();
+        second.acquire(Priority::Normal).await.unwrap();
+        assert_eq!(first.ratelimit().await.unwrap().remaining(), 0);
+
+        // The slots are used up by both handles together
+        second.acquire(Priority::Normal).await.unwrap();
+        assert_eq!(start.elapsed().as_secs(), 10);
+        assert_eq!(first.ratelimit().await.unwrap().remaining(), 1);
+    }
+}
@@ -0,0 +1,456 @@
+//! Module for sharing a ratelimit between processes through the file system.
+
+use super::backend::RatelimitBackend;
+use super::queue::Queue;
+use super::strategy::Window;
+use super::{Ratelimit, RatelimitBuilder};
+use crate::http::{blocking, BoxFuture, Priority, RatelimitHeaders};
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{delay_for, Duration, Instant};
+
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A ratelimit shared between processes on the same host.
+///
+/// The state is stored as JSON at the given path, next to a lock file guarding it. The lock is
+/// held through the file locking of the OS, so it's released even if a process crashes. Every
+/// process has to configure the ratelimit the same way. A missing or unreadable state file is
+/// treated as a fresh ratelimit.
+///
+/// Within a process, clones share the same ratelimit and requests are queued fairly. Across
+/// processes, the order in which waiting requests are let through is unspecified.
+///
+/// ## Example
+/// ```rust
+/// use r6stats_client::http::FileRatelimit;
+/// use r6stats_client::Client;
+///
+/// let ratelimit = FileRatelimit::new("/tmp/r6stats-ratelimit.json", |r| r.limit(60));
+///
+/// let client = Client::builder("TOKEN")
+///     .ratelimit_backend(ratelimit)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FileRatelimit {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    path: PathBuf,
+    lock_path: PathBuf,
+    /// The ratelimit used when there is no stored state yet.
+    initial: Ratelimit,
+    queue: Queue,
+}
+
+impl FileRatelimit {
+    /// Returns a ratelimit stored at `path`.
+    ///
+    /// # Args
+    ///
+    /// - `path` - The path of the state file, the lock file is created next to it
+    /// - `op` - Closure for building the [`Ratelimit`]
+    ///
+    /// [`Ratelimit`]: struct.Ratelimit.html
+    pub fn new<F>(path: impl Into<PathBuf>, op: F) -> Self
+    where
+        F: FnOnce(RatelimitBuilder) -> RatelimitBuilder,
+    {
+        let path = path.into();
+        let initial = op(RatelimitBuilder::new()).build();
+        let mut lock_path = OsString::from(path.as_os_str());
+        lock_path.push(".lock");
+
+        Self {
+            inner: Arc::new(Inner {
+                path,
+                lock_path: lock_path.into(),
+                queue: Queue::new(initial.priority_aging),
+                initial,
+            }),
+        }
+    }
+
+    /// The path of the state file.
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    async fn acquire_slot(&self, priority: Priority) -> Result<(), Error> {
+        if self.inner.initial.limit == 0 {
+            return Ok(());
+        }
+
+        let _turn = self.inner.queue.enter(priority).await;
+
+        loop {
+            match self.with_state(Ratelimit::take_slot).await? {
+                Ok(()) => return Ok(()),
+                Err(delay) => delay_for(delay).await,
+            }
+        }
+    }
+
+    /// Runs `op` on the stored ratelimit while holding the lock.
+    async fn with_state<T, F>(&self, op: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Ratelimit) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let lock = self.lock().await?;
+        let inner = Arc::clone(&self.inner);
+
+        blocking(move || {
+            let mut ratelimit = inner.load();
+            let result = op(&mut ratelimit);
+            inner.store(&ratelimit)?;
+
+            drop(lock);
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn lock(&self) -> Result<File, Error> {
+        loop {
+            let inner = Arc::clone(&self.inner);
+            if let Some(lock) = blocking(move || inner.try_lock()).await? {
+                return Ok(lock);
+            }
+
+            delay_for(LOCK_POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Inner {
+    /// Returns the locked lock file, or `None` if another process holds the lock.
+    ///
+    /// The lock is released when the file is closed.
+    fn try_lock(&self) -> Result<Option<File>, Error> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.lock_path)?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(file)),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    fn load(&self) -> Ratelimit {
+        let mut ratelimit = self.initial.clone();
+
+        let stored = fs::read(&self.path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Stored>(&bytes).ok());
+
+        if let Some(stored) = stored {
+            stored.apply(&mut ratelimit, Clock::now());
+        }
+
+        ratelimit
+    }
+
+    fn store(&self, ratelimit: &Ratelimit) -> Result<(), Error> {
+        let stored = Stored::new(ratelimit, Clock::now());
+
+        // Written to a temporary file first, so the state file is never seen half-written
+        let mut tmp_path = OsString::from(self.path.as_os_str());
+        tmp_path.push(".tmp");
+
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(&stored)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl RatelimitBackend for FileRatelimit {
+    fn acquire(&self, priority: Priority) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(self.acquire_slot(priority))
+    }
+
+    fn update(&self, headers: RatelimitHeaders) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            if self.inner.initial.limit == 0 {
+                return Ok(());
+            }
+
+            self.with_state(move |r| r.update(&headers)).await
+        })
+    }
+
+    fn exhaust(&self) -> BoxFuture<'_, Result<bool, Error>> {
+        Box::pin(async move {
+            if self.inner.initial.limit == 0 {
+                return Ok(false);
+            }
+
+            self.with_state(Ratelimit::exhaust).await
+        })
+    }
+
+    fn ratelimit(&self) -> BoxFuture<'_, Result<Ratelimit, Error>> {
+        Box::pin(async move {
+            if self.inner.initial.limit == 0 {
+                return Ok(self.inner.initial.clone());
+            }
+
+            self.with_state(|r| r.clone()).await
+        })
+    }
+}
+
+/// Converts between the monotonic time of this process and wall-clock time.
+#[derive(Copy, Clone)]
+struct Clock {
+    now: Instant,
+    system: SystemTime,
+}
+
+impl Clock {
+    fn now() -> Self {
+        Self {
+            now: Instant::now(),
+            system: SystemTime::now(),
+        }
+    }
+
+    /// Milliseconds since the unix epoch.
+    fn unix_millis(self, at: Instant) -> u64 {
+        let system = match at.checked_duration_since(self.now) {
+            Some(ahead) => self.system + ahead,
+            None => self.system - (self.now - at),
+        };
+
+        system
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+
+    /// Returns `None` if the time lies too far in the past to be represented.
+    fn instant(self, millis: u64) -> Option<Instant> {
+        let system = UNIX_EPOCH + Duration::from_millis(millis);
+
+        match system.duration_since(self.system) {
+            Ok(ahead) => Some(self.now + ahead),
+            Err(e) => self.now.checked_sub(e.duration()),
+        }
+    }
+}
+
+/// The state file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Stored {
+    limit: u16,
+    window: StoredWindow,
+    blocked_until: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+enum StoredWindow {
+    FixedWindow { remaining: u16, reset_at: u64 },
+    SlidingLog { log: Vec<u64> },
+    TokenBucket { burst: u16, full_at: u64 },
+}
+
+impl Stored {
+    fn new(ratelimit: &Ratelimit, clock: Clock) -> Self {
+        let window = match &ratelimit.window {
+            Window::Fixed {
+                remaining,
+                reset_at,
+            } => StoredWindow::FixedWindow {
+                remaining: *remaining,
+                reset_at: clock.unix_millis(*reset_at),
+            },
+            Window::Sliding { log } => StoredWindow::SlidingLog {
+                log: log.iter().map(|&t| clock.unix_millis(t)).collect(),
+            },
+            Window::Bucket { burst, full_at } => StoredWindow::TokenBucket {
+                burst: *burst,
+                full_at: clock.unix_millis(*full_at),
+            },
+        };
+
+        Self {
+            limit: ratelimit.limit,
+            window,
+            blocked_until: ratelimit.blocked_until.map(|t| clock.unix_millis(t)),
+        }
+    }
+
+    /// Restores the stored state onto `ratelimit`.
+    ///
+    /// Left untouched if the state was stored with another strategy.
+    fn apply(self, ratelimit: &mut Ratelimit, clock: Clock) {
+        // Times which can't be represented lie in the past, where they have no effect
+        let past = |millis| clock.instant(millis).unwrap_or(clock.now);
+
+        let window = match (&ratelimit.window, self.window) {
+            (
+                Window::Fixed { .. },
+                StoredWindow::FixedWindow {
+                    remaining,
+                    reset_at,
+                },
+            ) => Window::Fixed {
+                remaining,
+                reset_at: past(reset_at),
+            },
+            (Window::Sliding { .. }, StoredWindow::SlidingLog { log }) => Window::Sliding {
+                log: log
+                    .into_iter()
+                    .filter_map(|t| clock.instant(t))
+                    .filter(|&t| t + ratelimit.interval > clock.now)
+                    .collect(),
+            },
+            (Window::Bucket { burst, .. }, StoredWindow::TokenBucket { full_at, .. }) => {
+                Window::Bucket {
+                    burst: *burst,
+                    full_at: past(full_at),
+                }
+            }
+            _ => return,
+        };
+
+        ratelimit.limit = self.limit;
+        ratelimit.window = window;
+        ratelimit.blocked_until = self.blocked_until.map(past);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileRatelimit;
+    use crate::http::{Priority, RatelimitBackend, Strategy};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use tokio::time::{delay_for, Duration, Instant};
+
+    fn state_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("r6stats_client-{}-{}.json", process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn test_shared_between_instances() {
+        let path = state_path("shared");
+        let interval = Duration::from_millis(300);
+
+        // Stand-ins for two processes
+        let first = FileRatelimit::new(&path, |r| r.limit(2).interval(interval));
+        let second = FileRatelimit::new(&path, |r| r.limit(2).interval(interval));
+
+        let start = Instant::now();
+        first.acquire(Priority::Normal).await.unwrap();
+        second.acquire(Priority::Normal).await.unwrap();
+        assert_eq!(first.ratelimit().await.unwrap().remaining(), 0);
+
+        second.acquire(Priority::Normal).await.unwrap();
+        assert!(start.elapsed() >= interval - Duration::from_millis(5));
+        assert_eq!(first.ratelimit().await.unwrap().remaining(), 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_strategies_round_trip() {
+        let strategies = [
+            Strategy::FixedWindow,
+            Strategy::SlidingLog,
+            Strategy::TokenBucket { burst: 3 },
+        ];
+
+        for (i, &strategy) in strategies.iter().enumerate() {
+            let path = state_path(&format!("strategy-{}", i));
+            let op = |r: crate::http::RatelimitBuilder| {
+                r.limit(3)
+                    .interval(Duration::from_secs(60))
+                    .strategy(strategy)
+            };
+
+            FileRatelimit::new(&path, op)
+                .acquire(Priority::Normal)
+                .await
+                .unwrap();
+
+            let reloaded = FileRatelimit::new(&path, op).ratelimit().await.unwrap();
+            assert_eq!(reloaded.remaining(), 2, "{:?}", strategy);
+
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_state() {
+        let path = state_path("corrupt");
+        fs::write(&path, "{ not json").unwrap();
+
+        let ratelimit = FileRatelimit::new(&path, |r| r.limit(5));
+        ratelimit.acquire(Priority::Normal).await.unwrap();
+
+        assert_eq!(ratelimit.ratelimit().await.unwrap().remaining(), 4);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_leftover_lock_file() {
+        let path = state_path("leftover");
+        let ratelimit = FileRatelimit::new(&path, |r| r.limit(5));
+
+        // Left behind by a crashed process, which released the lock by exiting
+        fs::File::create(&ratelimit.inner.lock_path).unwrap();
+
+        ratelimit.acquire(Priority::Normal).await.unwrap();
+        assert_eq!(ratelimit.ratelimit().await.unwrap().remaining(), 4);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&ratelimit.inner.lock_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_lock() {
+        let path = state_path("locked");
+        let ratelimit = FileRatelimit::new(&path, |r| r.limit(5));
+
+        // Held by another process
+        let lock = fs::File::create(&ratelimit.inner.lock_path).unwrap();
+        lock.lock().unwrap();
+
+        let acquire = tokio::spawn({
+            let ratelimit = ratelimit.clone();
+            async move { ratelimit.acquire(Priority::Normal).await }
+        });
+        delay_for(Duration::from_millis(50)).await;
+        assert!(!path.exists());
+
+        drop(lock);
+        acquire.await.unwrap().unwrap();
+        assert_eq!(ratelimit.ratelimit().await.unwrap().remaining(), 4);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&ratelimit.inner.lock_path).unwrap();
+    }
+}
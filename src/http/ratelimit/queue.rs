@@ -0,0 +1,264 @@
+//! Module for queueing requests fairly while they wait for the ratelimit.
+
+use crate::http::Priority;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+
+const LANES: usize = 3;
+
+/// A priority queue letting one request at a time through.
+///
+/// Requests with a higher priority go first, requests of the same priority in the order they
+/// entered the queue. The priority of a waiting request rises by one level every `aging`, so
+/// requests with a low priority aren't starved.
+///
+/// Dropping a waiting request removes it from the queue. A request which is dropped right after
+/// its turn came passes the turn on to the next one.
+#[derive(Debug)]
+pub(super) struct Queue {
+    inner: Mutex<Inner>,
+    aging: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    busy: bool,
+    lanes: [VecDeque<Waiting>; LANES],
+}
+
+#[derive(Debug)]
+struct Waiting {
+    since: Instant,
+    tx: oneshot::Sender<()>,
+}
+
+impl Queue {
+    pub fn new(aging: Duration) -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            aging: aging.max(Duration::from_millis(1)),
+        }
+    }
+
+    /// Waits until the request is the next one to be let through.
+    pub async fn enter(&self, priority: Priority) -> Turn<'_> {
+        let rx = {
+            let mut inner = self.lock();
+            if !inner.busy {
+                inner.busy = true;
+                return Turn { queue: self };
+            }
+
+            let (tx, rx) = oneshot::channel();
+            inner.lanes[priority.level()].push_back(Waiting {
+                since: Instant::now(),
+                tx,
+            });
+            rx
+        };
+
+        let mut waiter = Waiter {
+            queue: self,
+            rx: Some(rx),
+        };
+
+        // The senders are only dropped along with the queue, which outlives the waiter
+        let _ = waiter.rx.as_mut().unwrap().await;
+        waiter.rx = None;
+
+        Turn { queue: self }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Hands the turn to the next request which is still waiting.
+    fn release(&self, inner: &mut Inner) {
+        let now = Instant::now();
+
+        while let Some(lane) = self.next_lane(inner, now) {
+            let waiting = inner.lanes[lane].pop_front().unwrap();
+            if waiting.tx.send(()).is_ok() {
+                return;
+            }
+        }
+
+        inner.busy = false;
+    }
+
+    /// The lane of the request to let through next.
+    fn next_lane(&self, inner: &Inner, now: Instant) -> Option<usize> {
+        let aging = self.aging.as_nanos();
+
+        (0..LANES)
+            .filter_map(|lane| {
+                let front = inner.lanes[lane].front()?;
+                let aged = (now - front.since).as_nanos() / aging;
+                Some((lane, aged + lane as u128, front.since))
+            })
+            .max_by_key(|&(_, level, since)| (level, Reverse(since)))
+            .map(|(lane, ..)| lane)
+    }
+}
+
+/// The turn of a request, passed on when dropped.
+#[derive(Debug)]
+pub(super) struct Turn<'a> {
+    queue: &'a Queue,
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        let mut inner = self.queue.lock();
+        self.queue.release(&mut inner);
+    }
+}
+
+/// A request waiting for its turn.
+struct Waiter<'a> {
+    queue: &'a Queue,
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            // Turns are handed out while holding the lock, so closing the channel under the
+            // lock either keeps the turn from being handed to us or reveals that it already was.
+            let mut inner = self.queue.lock();
+            rx.close();
+
+            if rx.try_recv().is_ok() {
+                self.queue.release(&mut inner);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Queue;
+    use crate::http::Priority;
+    use std::sync::{Arc, Mutex};
+    use tokio::task::yield_now;
+    use tokio::time::{delay_for, timeout, Duration};
+
+    const AGING: Duration = Duration::from_secs(30);
+
+    /// Queues up requests of the priorities behind a busy queue, and returns the order in which
+    /// they were let through.
+    async fn run_queue(queue: &Arc<Queue>, priorities: &[Priority]) -> Vec<usize> {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let first = queue.enter(Priority::Normal).await;
+
+        let mut handles = Vec::new();
+        for (i, &priority) in priorities.iter().enumerate() {
+            let queue = queue.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _turn = queue.enter(priority).await;
+                order.lock().unwrap().push(i);
+                let () = yield_now().await;
+            }));
+            // Let the task enter the queue before spawning the next one
+            let () = yield_now().await;
+        }
+
+        drop(first);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[tokio::test]
+    async fn test_fifo_order() {
+        let queue = Arc::new(Queue::new(AGING));
+        let order = run_queue(&queue, &[Priority::Normal; 5]).await;
+
+        assert_eq!(order, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_priority_order() {
+        let queue = Arc::new(Queue::new(AGING));
+        let priorities = [
+            Priority::Low,
+            Priority::Normal,
+            Priority::High,
+            Priority::Low,
+            Priority::High,
+        ];
+
+        let order = run_queue(&queue, &priorities).await;
+
+        assert_eq!(order, vec![2, 4, 1, 0, 3]);
+    }
+
+    /// Queues up a low priority request, and a high priority one after `wait`.
+    async fn aged_order(wait: Duration) -> Vec<Priority> {
+        let queue = Arc::new(Queue::new(AGING));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let first = queue.enter(Priority::Normal).await;
+
+        let mut handles = Vec::new();
+        for &priority in [Priority::Low, Priority::High].iter() {
+            if priority == Priority::High {
+                delay_for(wait).await;
+            }
+
+            let queue = queue.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _turn = queue.enter(priority).await;
+                order.lock().unwrap().push(priority);
+            }));
+            let () = yield_now().await;
+        }
+
+        drop(first);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[tokio::test]
+    async fn test_aging() {
+        tokio::time::pause();
+
+        // Raised to normal priority
+        let order = aged_order(AGING).await;
+        assert_eq!(order, vec![Priority::High, Priority::Low]);
+
+        // Raised to high priority, and waiting longer
+        let order = aged_order(AGING * 2).await;
+        assert_eq!(order, vec![Priority::Low, Priority::High]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter() {
+        tokio::time::pause();
+
+        let queue = Queue::new(AGING);
+        let first = queue.enter(Priority::Normal).await;
+
+        // Gives up while waiting behind `first`
+        assert!(timeout(Duration::from_secs(1), queue.enter(Priority::High))
+            .await
+            .is_err());
+
+        drop(first);
+        assert!(timeout(Duration::from_secs(1), queue.enter(Priority::Low))
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,209 @@
+//! Module for the ways of spreading requests over time.
+
+use std::collections::VecDeque;
+use std::iter;
+use tokio::time::{Duration, Instant};
+
+/// The way a [`Ratelimit`] spreads requests over time.
+///
+/// [`Ratelimit`]: struct.Ratelimit.html
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Strategy {
+    /// Lets `limit` requests through per interval. An interval starts with the first request
+    /// after the previous one is over.
+    ///
+    /// Up to twice the limit can be sent within one interval, when the requests are clustered
+    /// around the end of one interval and the start of the next.
+    #[default]
+    FixedWindow,
+    /// Lets a request through if less than `limit` requests were sent during the last interval.
+    ///
+    /// Never exceeds the limit within any interval, at the cost of remembering the time of the
+    /// last `limit` requests.
+    SlidingLog,
+    /// Refills a bucket holding up to `burst` tokens evenly with `limit` tokens per interval.
+    /// Each request takes a token.
+    ///
+    /// Allows bursts of `burst` requests after an idle period, while keeping the long-term rate
+    /// at `limit` requests per interval.
+    TokenBucket {
+        /// The capacity of the bucket. At least `1`.
+        burst: u16,
+    },
+}
+
+/// The state of a [`Strategy`].
+///
+/// [`Strategy`]: enum.Strategy.html
+#[derive(Clone, Debug)]
+pub(super) enum Window {
+    Fixed {
+        remaining: u16,
+        reset_at: Instant,
+    },
+    Sliding {
+        /// The times the requests of the last interval were sent at, oldest first.
+        log: VecDeque<Instant>,
+    },
+    Bucket {
+        burst: u16,
+        /// The time at which the bucket is full again.
+        full_at: Instant,
+    },
+}
+
+impl Window {
+    pub fn new(strategy: Strategy, limit: u16, now: Instant) -> Self {
+        match strategy {
+            Strategy::FixedWindow => Self::Fixed {
+                remaining: limit,
+                reset_at: now,
+            },
+            Strategy::SlidingLog => Self::Sliding {
+                log: VecDeque::with_capacity(limit as usize),
+            },
+            Strategy::TokenBucket { burst } => Self::Bucket {
+                burst: burst.max(1),
+                full_at: now,
+            },
+        }
+    }
+
+    /// Takes a slot for a request, or returns the duration until the next one is available.
+    pub fn take(&mut self, limit: u16, interval: Duration, now: Instant) -> Result<(), Duration> {
+        match self {
+            Self::Fixed {
+                remaining,
+                reset_at,
+            } => {
+                if now >= *reset_at {
+                    *reset_at = now + interval;
+                    *remaining = limit;
+                }
+
+                if *remaining == 0 {
+                    return Err(*reset_at - now);
+                }
+
+                *remaining -= 1;
+            }
+            Self::Sliding { log } => {
+                prune(log, interval, now);
+
+                if log.len() >= limit as usize {
+                    return Err(log[0] + interval - now);
+                }
+
+                log.push_back(now);
+            }
+            Self::Bucket { burst, full_at } => {
+                let token = token_interval(limit, interval);
+                let capacity = token * u32::from(*burst);
+                let next_full_at = (*full_at).max(now) + token;
+                let missing = next_full_at - now;
+
+                if missing > capacity {
+                    return Err(missing - capacity);
+                }
+
+                *full_at = next_full_at;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The number of requests which can be sent right away.
+    pub fn remaining(&self, limit: u16, interval: Duration, now: Instant) -> u16 {
+        match self {
+            Self::Fixed { remaining, .. } => *remaining,
+            Self::Sliding { log } => {
+                let live = log.iter().filter(|&&t| t + interval > now).count();
+                limit.saturating_sub(live as u16)
+            }
+            Self::Bucket { burst, full_at } => {
+                let token = token_interval(limit, interval);
+                let capacity = token * u32::from(*burst);
+                let missing = full_at.saturating_duration_since(now);
+                let tokens = capacity.checked_sub(missing).unwrap_or_default();
+                (tokens.as_nanos() / token.as_nanos()) as u16
+            }
+        }
+    }
+
+    /// The time at which all slots are available again.
+    pub fn reset_at(&self, interval: Duration, now: Instant) -> Instant {
+        match self {
+            Self::Fixed { reset_at, .. } => *reset_at,
+            Self::Sliding { log } => log.back().map_or(now, |&t| t + interval),
+            Self::Bucket { full_at, .. } => *full_at,
+        }
+    }
+
+    /// Uses up all slots.
+    pub fn exhaust(&mut self, limit: u16, interval: Duration, now: Instant) {
+        match self {
+            Self::Fixed {
+                remaining,
+                reset_at,
+            } => {
+                if now >= *reset_at {
+                    *reset_at = now + interval;
+                }
+                *remaining = 0;
+            }
+            Self::Sliding { log } => {
+                log.clear();
+                log.extend(iter::repeat_n(now, limit as usize));
+            }
+            Self::Bucket { burst, full_at } => {
+                *full_at = now + token_interval(limit, interval) * u32::from(*burst);
+            }
+        }
+    }
+
+    /// Sets the number of requests which can be sent right away.
+    pub fn set_remaining(&mut self, count: u16, limit: u16, interval: Duration, now: Instant) {
+        let count = count.min(limit);
+
+        match self {
+            Self::Fixed { remaining, .. } => *remaining = count,
+            Self::Sliding { log } => {
+                prune(log, interval, now);
+
+                let used = (limit - count) as usize;
+                while log.len() > used {
+                    log.pop_front();
+                }
+                log.extend(iter::repeat_n(now, used - log.len()));
+            }
+            Self::Bucket { burst, full_at } => {
+                let count = count.min(*burst);
+                *full_at = now + token_interval(limit, interval) * u32::from(*burst - count);
+            }
+        }
+    }
+
+    /// Sets the time at which the slots are available again.
+    ///
+    /// Only the fixed window has a reset time, the other strategies free up their slots one by
+    /// one.
+    pub fn set_reset_at(&mut self, at: Instant) {
+        if let Self::Fixed { reset_at, .. } = self {
+            *reset_at = at;
+        }
+    }
+}
+
+/// Removes the requests which were sent before the last interval.
+fn prune(log: &mut VecDeque<Instant>, interval: Duration, now: Instant) {
+    while log.front().is_some_and(|&t| t + interval <= now) {
+        log.pop_front();
+    }
+}
+
+/// The time it takes to refill a single token.
+fn token_interval(limit: u16, interval: Duration) -> Duration {
+    (interval / u32::from(limit.max(1))).max(Duration::from_nanos(1))
+}
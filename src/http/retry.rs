@@ -0,0 +1,366 @@
+//! Module for retrying failed requests.
+
+use crate::Error;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use tokio::time::Duration;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 1;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+const DEFAULT_STATUSES: [StatusCode; 5] = [
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+const DEFAULT_ERRORS: [RetryableError; 2] = [RetryableError::Timeout, RetryableError::Connect];
+
+/// Randomization applied to the backoff delay.
+///
+/// Spreads out the retries of multiple clients failing at the same time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Jitter {
+    /// Always wait the exact backoff delay.
+    None,
+    /// Wait a random duration between zero and the backoff delay.
+    Full,
+    /// Wait at least half of the backoff delay, plus a random duration of up to the other half.
+    Equal,
+}
+
+/// Kinds of request errors which can be retried.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RetryableError {
+    /// The request timed out.
+    Timeout,
+    /// The connection to the server couldn't be established.
+    Connect,
+    /// The request failed while being sent.
+    Request,
+    /// The response body couldn't be read.
+    Body,
+}
+
+impl RetryableError {
+    fn matches(self, err: &reqwest::Error) -> bool {
+        match self {
+            Self::Timeout => err.is_timeout(),
+            Self::Connect => err.is_connect(),
+            Self::Request => err.is_request(),
+            Self::Body => err.is_body(),
+        }
+    }
+}
+
+/// The builder for [`RetryPolicy`].
+///
+/// [`RetryPolicy`]: struct.RetryPolicy.html
+#[derive(Clone, Debug, Default)]
+pub struct RetryPolicyBuilder {
+    max_attempts: Option<u32>,
+    initial_backoff: Option<Duration>,
+    max_backoff: Option<Duration>,
+    multiplier: Option<f64>,
+    jitter: Option<Jitter>,
+    statuses: Option<Vec<StatusCode>>,
+    errors: Option<Vec<RetryableError>>,
+}
+
+impl RetryPolicyBuilder {
+    /// Returns a new builder.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of attempts, including the first one.
+    ///
+    /// Defaults to `1`, which disables retrying.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts.max(1));
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    ///
+    /// Defaults to 500 milliseconds.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = Some(backoff);
+        self
+    }
+
+    /// Sets the upper bound of the delay between two attempts.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = Some(backoff);
+        self
+    }
+
+    /// Sets the factor the delay grows by after each retry.
+    ///
+    /// Defaults to `2.0`.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = Some(multiplier.max(1.0));
+        self
+    }
+
+    /// Sets the [`Jitter`] applied to the delay.
+    ///
+    /// Defaults to [`Jitter::Full`].
+    ///
+    /// [`Jitter`]: enum.Jitter.html
+    /// [`Jitter::Full`]: enum.Jitter.html#variant.Full
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    /// Sets the status codes which are retried.
+    ///
+    /// Only statuses of transient failures can be retried, see [`Error::is_retryable`].
+    /// Defaults to `429`, `500`, `502`, `503` and `504`.
+    ///
+    /// [`Error::is_retryable`]: ../enum.Error.html#method.is_retryable
+    pub fn statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.statuses = Some(statuses.into_iter().collect());
+        self
+    }
+
+    /// Sets the kinds of request errors which are retried.
+    ///
+    /// Defaults to [`Timeout`] and [`Connect`].
+    ///
+    /// [`Timeout`]: enum.RetryableError.html#variant.Timeout
+    /// [`Connect`]: enum.RetryableError.html#variant.Connect
+    pub fn errors(mut self, errors: impl IntoIterator<Item = RetryableError>) -> Self {
+        self.errors = Some(errors.into_iter().collect());
+        self
+    }
+
+    /// Returns the finished [`RetryPolicy`].
+    ///
+    /// [`RetryPolicy`]: struct.RetryPolicy.html
+    pub(crate) fn build(self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            initial_backoff: self.initial_backoff.unwrap_or(DEFAULT_INITIAL_BACKOFF),
+            max_backoff: self.max_backoff.unwrap_or(DEFAULT_MAX_BACKOFF),
+            multiplier: self.multiplier.unwrap_or(DEFAULT_MULTIPLIER),
+            jitter: self.jitter.unwrap_or(Jitter::Full),
+            statuses: self.statuses.unwrap_or_else(|| DEFAULT_STATUSES.to_vec()),
+            errors: self.errors.unwrap_or_else(|| DEFAULT_ERRORS.to_vec()),
+        }
+    }
+}
+
+/// Decides whether and when failed requests are retried.
+///
+/// The delay before the `n`-th retry is `initial_backoff * multiplier^(n - 1)`, capped at
+/// `max_backoff` and randomized by the [`Jitter`].
+///
+/// [`Jitter`]: enum.Jitter.html
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: Jitter,
+    statuses: Vec<StatusCode>,
+    errors: Vec<RetryableError>,
+}
+
+impl RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The delay before the first retry.
+    pub fn initial_backoff(&self) -> Duration {
+        self.initial_backoff
+    }
+
+    /// The upper bound of the delay between two attempts.
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    /// The factor the delay grows by after each retry.
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    /// The [`Jitter`] applied to the delay.
+    ///
+    /// [`Jitter`]: enum.Jitter.html
+    pub fn jitter(&self) -> Jitter {
+        self.jitter
+    }
+
+    /// The status codes which are retried.
+    pub fn statuses(&self) -> &[StatusCode] {
+        &self.statuses
+    }
+
+    /// The kinds of request errors which are retried.
+    pub fn errors(&self) -> &[RetryableError] {
+        &self.errors
+    }
+
+    /// Returns true if the policy retries the error.
+    ///
+    /// Only transient errors are retried, see [`Error::is_retryable`], and of those the ones
+    /// matching the [`statuses`] and [`errors`] of the policy.
+    ///
+    /// [`Error::is_retryable`]: ../enum.Error.html#method.is_retryable
+    /// [`statuses`]: #method.statuses
+    /// [`errors`]: #method.errors
+    pub fn is_retryable(&self, err: &Error) -> bool {
+        if !err.is_retryable() {
+            return false;
+        }
+
+        if let Some(status) = err.status() {
+            return self.statuses.contains(&status);
+        }
+
+        match err {
+            Error::HttpError(err) => match err.request_error() {
+                Some(err) => self.errors.iter().any(|kind| kind.matches(err)),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the delay before the retry following the `attempt`-th attempt.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exp);
+        let delay = delay.min(self.max_backoff.as_secs_f64());
+
+        let delay = match self.jitter {
+            Jitter::None => delay,
+            Jitter::Full => delay * random_fraction(),
+            Jitter::Equal => delay / 2.0 + delay / 2.0 * random_fraction(),
+        };
+
+        Duration::from_secs_f64(delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicyBuilder::new().build()
+    }
+}
+
+/// Returns a random number in `[0, 1)`.
+fn random_fraction() -> f64 {
+    // Every `RandomState` is seeded with fresh random keys
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Jitter, RetryPolicyBuilder, RetryableError};
+    use crate::http::error::unsuccessful_request;
+    use crate::Error;
+    use bytes::Bytes;
+    use reqwest::StatusCode;
+    use tokio::time::Duration;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = RetryPolicyBuilder::new().build();
+
+        assert_eq!(policy.max_attempts(), 1);
+        assert_eq!(policy.jitter(), Jitter::Full);
+        assert!(policy.statuses().contains(&StatusCode::TOO_MANY_REQUESTS));
+        assert!(policy.errors().contains(&RetryableError::Timeout));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicyBuilder::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500))
+            .multiplier(3.0)
+            .jitter(Jitter::None)
+            .build();
+
+        let expected = [100, 300, 500, 500];
+
+        for (attempt, &millis) in (1..).zip(expected.iter()) {
+            assert_eq!(policy.backoff(attempt), Duration::from_millis(millis));
+        }
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let full = RetryPolicyBuilder::new()
+            .initial_backoff(Duration::from_secs(1))
+            .jitter(Jitter::Full)
+            .build();
+        let equal = RetryPolicyBuilder::new()
+            .initial_backoff(Duration::from_secs(1))
+            .jitter(Jitter::Equal)
+            .build();
+
+        for _ in 0..100 {
+            assert!(full.backoff(1) < Duration::from_secs(1));
+
+            let delay = equal.backoff(1);
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay < Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let policy = RetryPolicyBuilder::new()
+            .statuses(vec![StatusCode::BAD_GATEWAY])
+            .build();
+
+        let retryable = unsuccessful_request(
+            "http://localhost/",
+            StatusCode::BAD_GATEWAY,
+            None,
+            Bytes::new(),
+        );
+        let not_retryable = unsuccessful_request(
+            "http://localhost/",
+            StatusCode::NOT_FOUND,
+            None,
+            Bytes::new(),
+        );
+
+        assert!(policy.is_retryable(&retryable));
+        assert!(!policy.is_retryable(&not_retryable));
+        assert!(!policy.is_retryable(&Error::UsernameMalformed));
+
+        // Retryable, but not by this policy
+        let server_error = unsuccessful_request(
+            "http://localhost/",
+            StatusCode::SERVICE_UNAVAILABLE,
+            None,
+            Bytes::new(),
+        );
+        assert!(server_error.is_retryable());
+        assert!(!policy.is_retryable(&server_error));
+
+        // Never retried, whatever the policy says
+        let policy = RetryPolicyBuilder::new()
+            .statuses(vec![StatusCode::NOT_FOUND])
+            .build();
+        assert!(!not_retryable.is_retryable());
+        assert!(!policy.is_retryable(&not_retryable));
+    }
+}
@@ -1,12 +1,9 @@
-#[macro_use]
-mod macros;
-
 pub mod utils;
 
-#[cfg(feature = "ratelimiting")]
-mod cell;
+mod bit_eq;
 mod rc;
+mod stream;
 
-#[cfg(feature = "ratelimiting")]
-pub(crate) use self::cell::Cell;
+pub(crate) use self::bit_eq::{impl_bitwise_eq, BitEq};
 pub(crate) use self::rc::Rc;
+pub(crate) use self::stream::BoxStream;
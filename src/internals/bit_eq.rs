@@ -0,0 +1,50 @@
+/// Equality comparing floats by their bits, so `NaN` equals itself.
+pub(crate) trait BitEq {
+    fn bit_eq(&self, other: &Self) -> bool;
+}
+
+impl BitEq for f32 {
+    fn bit_eq(&self, other: &Self) -> bool {
+        self.to_bits() == other.to_bits()
+    }
+}
+
+impl BitEq for f64 {
+    fn bit_eq(&self, other: &Self) -> bool {
+        self.to_bits() == other.to_bits()
+    }
+}
+
+impl<T: BitEq> BitEq for Option<T> {
+    fn bit_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.bit_eq(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Implements `PartialEq` for a model, comparing the `floats` with [`BitEq`] and the other
+/// `fields` with `==`.
+///
+/// [`BitEq`]: trait.BitEq.html
+macro_rules! impl_bitwise_eq {
+    ($ty:ident { $($field:ident),* $(,)? } floats { $($float:ident),* $(,)? }) => {
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                // Destructured, so adding a field without comparing it doesn't compile
+                let Self {
+                    $($field,)*
+                    $($float,)*
+                } = self;
+
+                $(*$field == other.$field &&)*
+                $($crate::internals::BitEq::bit_eq($float, &other.$float) &&)*
+                true
+            }
+        }
+    };
+}
+
+pub(crate) use impl_bitwise_eq;
@@ -0,0 +1,8 @@
+use futures::Stream;
+use std::pin::Pin;
+
+#[cfg(not(feature = "threadsafe"))]
+pub(crate) type BoxStream<T> = Pin<Box<dyn Stream<Item = T>>>;
+
+#[cfg(feature = "threadsafe")]
+pub(crate) type BoxStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
@@ -1,5 +1,6 @@
 use crate::Error;
-use serde::{Deserialize, Deserializer};
+use reqwest::Url;
+use serde::{Deserialize, Deserializer, Serializer};
 
 pub fn check_username(username: &str) -> Result<(), Error> {
     if valid_username(username) {
@@ -21,6 +22,18 @@ fn valid_username(username: &str) -> bool {
         .all(|c| c.is_alphanumeric() || ALLOWED_SPECIAL_CHARS.contains(c))
 }
 
+/// Appends `segments` to the path of `base`.
+///
+/// Each segment is percent-encoded, so it can't escape its position in the path.
+pub fn join_segments(base: &Url, segments: &[&str]) -> Url {
+    let mut url = base.clone();
+    url.path_segments_mut()
+        .expect("Base URL cannot be a base.")
+        .pop_if_empty()
+        .extend(segments);
+    url
+}
+
 pub fn serde_parse_f64_option<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
 where
     D: Deserializer<'de>,
@@ -33,13 +46,24 @@ where
     }
 }
 
+pub fn serde_display_f64_option<S>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(f) => serializer.collect_str(f),
+        None => serializer.serialize_none(),
+    }
+}
+
 fn parse_f64<E: serde::de::Error>(s: &str) -> Result<f64, E> {
     s.parse().map_err(serde::de::Error::custom)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{check_username, serde_parse_f64_option};
+    use super::{check_username, join_segments, serde_display_f64_option, serde_parse_f64_option};
+    use reqwest::Url;
 
     #[test]
     fn test_check_username() {
@@ -53,11 +77,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_join_segments() {
+        let expected = "http://localhost:8080/api/a/b%20c";
+        let bases = ["http://localhost:8080/api", "http://localhost:8080/api/"];
+
+        for &base in bases.iter() {
+            let base = Url::parse(base).unwrap();
+            assert_eq!(join_segments(&base, &["a", "b c"]).as_str(), expected);
+        }
+    }
+
     #[test]
     fn test_parse_f64() {
-        #[derive(serde::Deserialize)]
+        #[derive(serde::Deserialize, serde::Serialize)]
         struct Test {
             #[serde(deserialize_with = "serde_parse_f64_option")]
+            #[serde(serialize_with = "serde_display_f64_option")]
             value: Option<f64>,
         }
 
@@ -69,5 +105,9 @@ mod tests {
 
         let test: Test = serde_json::from_str(json).unwrap();
         assert_eq!(test.value.unwrap(), 0.000017);
+        assert_eq!(
+            serde_json::to_string(&test).unwrap(),
+            r#"{"value":"0.000017"}"#
+        );
     }
 }
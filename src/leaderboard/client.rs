@@ -1,18 +1,70 @@
 use super::model::Leaderboard;
+use crate::http::{Endpoint, Priority, Request};
 use crate::internals::Rc;
 use crate::leaderboard::http::RouteBuilder;
+use crate::stats::Resolver;
 use crate::{Error, Http, Platform, Region};
-use reqwest::Response;
+use bytes::Bytes;
 
 /// Client for the leaderboard endpoint.
 #[derive(Clone, Debug)]
 pub struct Client {
     http: Rc<Http>,
+    priority: Priority,
+    force_refresh: bool,
+    resolver: Rc<Resolver>,
 }
 
 impl Client {
-    pub(crate) fn new(http: Rc<Http>) -> Self {
-        Self { http }
+    pub(crate) fn new(http: Rc<Http>, resolver: Rc<Resolver>) -> Self {
+        Self {
+            http,
+            priority: Priority::default(),
+            force_refresh: false,
+            resolver,
+        }
+    }
+
+    /// Returns a client whose requests have the [`Priority`] while waiting for the ratelimit.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use r6stats_client::{Client, Platform};
+    /// use r6stats_client::http::Priority;
+    ///
+    /// # async fn run(client: Client) -> Result<(), r6stats_client::Error> {
+    /// let leaderboard = client
+    ///     .leaderboard()
+    ///     .with_priority(Priority::High)
+    ///     .get(Platform::Pc, None)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Priority`]: ../http/enum.Priority.html
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client whose requests skip the response cache.
+    ///
+    /// The responses are still cached for later requests. Has no effect if caching is disabled.
+    pub fn force_refresh(&self) -> Self {
+        Self {
+            force_refresh: true,
+            ..self.clone()
+        }
+    }
+
+    /// The [`Priority`] of the requests of this client.
+    ///
+    /// [`Priority`]: ../http/enum.Priority.html
+    pub fn priority(&self) -> Priority {
+        self.priority
     }
 
     /// Gets the current leaderboard.
@@ -29,19 +81,28 @@ impl Client {
         platform: Platform,
         region: Option<Region>,
     ) -> Result<Leaderboard, Error> {
-        let response = self.request(platform, region).await?;
-        let bytes = response.bytes().await?;
-        Ok(serde_json::from_slice(&bytes)?)
+        let bytes = self.request(platform, region).await?;
+        let leaderboard: Leaderboard = serde_json::from_slice(&bytes)?;
+
+        for player in &leaderboard.players {
+            self.resolver.insert(&*player.ubisoft_id, &*player.username);
+        }
+        Ok(leaderboard)
     }
 
-    async fn request(&self, platform: Platform, region: Option<Region>) -> Result<Response, Error> {
+    async fn request(&self, platform: Platform, region: Option<Region>) -> Result<Bytes, Error> {
         let route = RouteBuilder::new()
             .platform(platform)
             .region(region)
             .build();
 
-        let path = route.path();
+        let request = Request {
+            url: route.url(self.http.base_url()),
+            endpoint: Endpoint::Leaderboard,
+            priority: self.priority,
+            force_refresh: self.force_refresh,
+        };
 
-        self.http.request(&path).await
+        self.http.request(&request).await
     }
 }
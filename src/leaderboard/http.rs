@@ -1,4 +1,6 @@
+use crate::internals::utils::join_segments;
 use crate::{Platform, Region};
+use reqwest::Url;
 
 #[derive(Clone, Debug, Default)]
 pub(crate) struct RouteBuilder {
@@ -38,26 +40,28 @@ pub(crate) struct RouteInfo {
 }
 
 impl RouteInfo {
-    pub fn path(&self) -> String {
+    pub fn url(&self, base: &Url) -> Url {
         let region = self.region.map(Region::as_str).unwrap_or("all");
-        format!(
-            api!("/leaderboard/{p}/{r}"),
-            p = self.platform.as_str(),
-            r = region
-        )
+        join_segments(base, &["leaderboard", self.platform.as_str(), region])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::RouteBuilder;
+    use crate::http::DEFAULT_BASE_URL;
     use crate::{Platform, Region};
+    use reqwest::Url;
 
     #[test]
     fn test_routing_all_regions() {
         let route = RouteBuilder::new().platform(Platform::Pc).build();
 
-        assert_eq!(route.path(), api!("/leaderboard/pc/all"));
+        let base = Url::parse(DEFAULT_BASE_URL).unwrap();
+        assert_eq!(
+            route.url(&base).as_str(),
+            "https://api2.r6stats.com/public-api/leaderboard/pc/all"
+        );
     }
 
     #[test]
@@ -67,6 +71,24 @@ mod tests {
             .region(Some(Region::Apac))
             .build();
 
-        assert_eq!(route.path(), api!("/leaderboard/pc/apac"));
+        let base = Url::parse(DEFAULT_BASE_URL).unwrap();
+        assert_eq!(
+            route.url(&base).as_str(),
+            "https://api2.r6stats.com/public-api/leaderboard/pc/apac"
+        );
+    }
+
+    #[test]
+    fn test_routing_custom_base_url() {
+        let route = RouteBuilder::new()
+            .platform(Platform::Xbox)
+            .region(Some(Region::Emea))
+            .build();
+
+        let base = Url::parse("http://localhost:3000").unwrap();
+        assert_eq!(
+            route.url(&base).as_str(),
+            "http://localhost:3000/leaderboard/xbox/emea"
+        );
     }
 }
@@ -1,9 +1,10 @@
 //! Models for leaderboard endpoint.
 
-use serde::Deserialize;
+use crate::internals::impl_bitwise_eq;
+use serde::{Deserialize, Serialize};
 
 /// The leaderboard.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(transparent)]
 #[non_exhaustive]
 pub struct Leaderboard {
@@ -12,7 +13,7 @@ pub struct Leaderboard {
 }
 
 /// Deserialized player.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct Player {
     pub username: String,
@@ -26,11 +27,31 @@ pub struct Player {
     pub position: u16,
 }
 
+impl_bitwise_eq!(Player {
+    username,
+    platform,
+    ubisoft_id,
+    uplay_id,
+    avatar_url_146,
+    avatar_url_256,
+    stats,
+    position,
+} floats {
+    score,
+});
+
 /// Deserialized stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct Stats {
     pub level: u16,
     pub kd: f32,
     pub wl: f32,
 }
+
+impl_bitwise_eq!(Stats {
+    level,
+} floats {
+    kd,
+    wl,
+});
@@ -1,9 +1,9 @@
 //! # r6stats Client
 //!
-//! This crate provides a client for the r6stats API. It supports the `/stats` and the `/leaderboard` endpoint.
+//! This crate provides a client for the r6stats API. It supports the `/stats`, the `/leaderboard` and the `/search` endpoint.
 //!
 //! ## Example
-//! ```rust
+//! ```no_run
 //! use r6stats_client::{Client, Platform, Region};
 //! # use std::env;
 //!
@@ -31,26 +31,32 @@
 //! ## Features
 //!
 //! - `ratelimiting` (default): Enables pre-ratelimiting **before** sending requests to prevent HTTP-429 Errors.
-//! **Note**: Ratelimits are enforced by the server either way.
+//!   **Note**: Ratelimits are enforced by the server either way.
 //! - `threadsafe`: Makes [`Client`] threadsafe (`Send` + `Sync`).
+//! - `test-util`: Enables the [`test_util`] module with a local mock server for offline tests.
 //!
 //! [examples]: https://github.com/Jan561/r6stats_client/tree/master/examples
 //! [`Client`]: client/struct.Client.html
+//! [`test_util`]: test_util/index.html
 
-#[macro_use]
 mod internals;
 
 pub mod http;
 pub mod leaderboard;
+pub mod search;
 pub mod stats;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 mod client;
 mod error;
+mod fingerprint;
 mod platform;
 mod region;
 
-pub use crate::client::Client;
+pub use crate::client::{Client, ClientBuilder};
 pub use crate::error::Error;
+pub use crate::fingerprint::Fingerprint;
 pub use crate::platform::Platform;
 pub use crate::region::Region;
 
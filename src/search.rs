@@ -0,0 +1,12 @@
+//! The player search endpoint of the api.
+//!
+//! The `/search/{query}/{platform}` route isn't part of the public documentation of the api, so
+//! the route and its response are unverified. The response is assumed to list the player info
+//! of the stats responses.
+
+pub mod model;
+
+mod client;
+mod http;
+
+pub use self::client::Client;
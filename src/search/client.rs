@@ -0,0 +1,105 @@
+use super::model::SearchResults;
+use crate::http::{Endpoint, Priority, Request};
+use crate::internals::Rc;
+use crate::search::http::RouteBuilder;
+use crate::stats::Resolver;
+use crate::{Error, Http, Platform};
+use bytes::Bytes;
+
+/// Client for the player search endpoint.
+#[derive(Clone, Debug)]
+pub struct Client {
+    http: Rc<Http>,
+    priority: Priority,
+    force_refresh: bool,
+    resolver: Rc<Resolver>,
+}
+
+impl Client {
+    pub(crate) fn new(http: Rc<Http>, resolver: Rc<Resolver>) -> Self {
+        Self {
+            http,
+            priority: Priority::default(),
+            force_refresh: false,
+            resolver,
+        }
+    }
+
+    /// Returns a client whose requests have the [`Priority`] while waiting for the ratelimit.
+    ///
+    /// [`Priority`]: ../http/enum.Priority.html
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client whose requests skip the response cache.
+    ///
+    /// The responses are still cached for later requests. Has no effect if caching is disabled.
+    pub fn force_refresh(&self) -> Self {
+        Self {
+            force_refresh: true,
+            ..self.clone()
+        }
+    }
+
+    /// The [`Priority`] of the requests of this client.
+    ///
+    /// [`Priority`]: ../http/enum.Priority.html
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Searches for players whose username matches the partial `query`.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use r6stats_client::{Client, Platform};
+    /// # async fn run(client: Client) -> Result<(), r6stats_client::Error> {
+    /// let results = client.search().players("pengu", Some(Platform::Pc)).await?;
+    ///
+    /// for candidate in results.players {
+    ///     println!("{} ({})", candidate.username, candidate.ubisoft_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Args
+    ///
+    /// - `query`: The partial username
+    /// - `platform`: Optionally filter for a [`Platform`]
+    ///
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub async fn players(
+        &self,
+        query: impl AsRef<str>,
+        platform: Option<Platform>,
+    ) -> Result<SearchResults, Error> {
+        let bytes = self.request(query.as_ref(), platform).await?;
+        let results: SearchResults = serde_json::from_slice(&bytes)?;
+
+        for player in &results.players {
+            self.resolver.insert(&*player.ubisoft_id, &*player.username);
+        }
+        Ok(results)
+    }
+
+    async fn request(&self, query: &str, platform: Option<Platform>) -> Result<Bytes, Error> {
+        let route = RouteBuilder::new()
+            .query(query)
+            .platform(platform)
+            .build()?;
+
+        let request = Request {
+            url: route.url(self.http.base_url()),
+            endpoint: Endpoint::Search,
+            priority: self.priority,
+            force_refresh: self.force_refresh,
+        };
+
+        self.http.request(&request).await
+    }
+}
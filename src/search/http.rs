@@ -0,0 +1,118 @@
+use crate::internals::utils::join_segments;
+use crate::{Error, Platform};
+use reqwest::Url;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RouteBuilder {
+    query: Option<String>,
+    platform: Option<Platform>,
+}
+
+impl RouteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn query(mut self, query: &str) -> Self {
+        self.query = Some(query.to_string());
+        self
+    }
+
+    pub fn platform(mut self, platform: Option<Platform>) -> Self {
+        self.platform = platform;
+        self
+    }
+
+    pub fn build(self) -> Result<RouteInfo, Error> {
+        let query = self.query.expect("Error creating route: Query missing.");
+
+        check_query(&query)?;
+
+        Ok(RouteInfo {
+            query,
+            platform: self.platform,
+        })
+    }
+}
+
+/// Checks the query is usable as a path segment.
+///
+/// Any other character is allowed, since the segment is percent-encoded. The dot segments are
+/// rejected, as they would be resolved away instead of being encoded.
+fn check_query(query: &str) -> Result<(), Error> {
+    if query.trim().is_empty() || query == "." || query == ".." {
+        Err(Error::QueryMalformed)
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RouteInfo {
+    pub query: String,
+    pub platform: Option<Platform>,
+}
+
+impl RouteInfo {
+    pub fn url(&self, base: &Url) -> Url {
+        let platform = self.platform.map(Platform::as_str).unwrap_or("all");
+        join_segments(base, &["search", &self.query, platform])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RouteBuilder;
+    use crate::http::DEFAULT_BASE_URL;
+    use crate::{Error, Platform};
+    use reqwest::Url;
+
+    #[test]
+    fn test_routing_all_platforms() {
+        let route = RouteBuilder::new().query("pengu").build().unwrap();
+
+        let base = Url::parse(DEFAULT_BASE_URL).unwrap();
+        assert_eq!(
+            route.url(&base).as_str(),
+            "https://api2.r6stats.com/public-api/search/pengu/all"
+        );
+    }
+
+    #[test]
+    fn test_routing_with_platform() {
+        let route = RouteBuilder::new()
+            .query("i jefe")
+            .platform(Some(Platform::Xbox))
+            .build()
+            .unwrap();
+
+        let base = Url::parse(DEFAULT_BASE_URL).unwrap();
+        assert_eq!(
+            route.url(&base).as_str(),
+            "https://api2.r6stats.com/public-api/search/i%20jefe/xbox"
+        );
+    }
+
+    #[test]
+    fn test_encoded_query() {
+        let base = Url::parse(DEFAULT_BASE_URL).unwrap();
+        let url = |query| RouteBuilder::new().query(query).build().unwrap().url(&base);
+
+        assert_eq!(
+            url("pengu/../g2?#").as_str(),
+            "https://api2.r6stats.com/public-api/search/pengu%2F..%2Fg2%3F%23/all"
+        );
+        assert_eq!(
+            url("ñ%").as_str(),
+            "https://api2.r6stats.com/public-api/search/%C3%B1%25/all"
+        );
+    }
+
+    #[test]
+    fn test_malformed_query() {
+        for &query in ["", "  ", ".", ".."].iter() {
+            let err = RouteBuilder::new().query(query).build().unwrap_err();
+            assert!(matches!(err, Error::QueryMalformed));
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Models for player search endpoint.
+
+use serde::{Deserialize, Serialize};
+
+/// The players matching a search.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(transparent)]
+#[non_exhaustive]
+pub struct SearchResults {
+    /// The matching players, best match first
+    pub players: Vec<Candidate>,
+}
+
+/// Deserialized player matching a search.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Candidate {
+    pub username: String,
+    pub platform: String,
+    pub ubisoft_id: String,
+    pub uplay_id: Option<String>,
+    pub avatar_url_146: Option<String>,
+    pub avatar_url_256: Option<String>,
+}
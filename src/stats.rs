@@ -2,10 +2,15 @@
 
 pub mod model;
 
+mod batch;
 mod client;
 mod http;
 mod kind;
+mod lenient;
+mod player;
 
+pub use self::batch::{Batch, PlayerResult};
 pub use self::client::Client;
-
-use self::kind::Kind;
+pub use self::kind::Kind;
+pub use self::lenient::{Issue, Lenient};
+pub use self::player::{PlayerId, Resolver};
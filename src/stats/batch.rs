@@ -0,0 +1,101 @@
+//! Module for looking up the stats of many players at once.
+
+use super::PlayerId;
+use crate::internals::BoxStream;
+use crate::{Error, Platform};
+use futures::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The stats of a player looked up in a [`Batch`].
+///
+/// [`Batch`]: struct.Batch.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PlayerResult<T> {
+    pub player: PlayerId,
+    pub platform: Platform,
+    pub result: Result<T, Error>,
+}
+
+/// A stream of the stats of many players, in the order the lookups complete.
+///
+/// The lookups are sent concurrently, up to the limit set with [`Client::with_max_in_flight`],
+/// and wait for the ratelimit like any other request. Nothing is sent until the stream is
+/// polled. Dropping the stream cancels the pending lookups.
+///
+/// ## Example
+/// ```rust,no_run
+/// # use r6stats_client::{Client, Platform};
+/// use futures::StreamExt;
+///
+/// # async fn run(client: Client) {
+/// let players = vec![("pengu.g2", Platform::Pc), ("Beaulo.TSM", Platform::Pc)];
+/// let mut batch = client.stats().with_max_in_flight(2).generic_many(players);
+///
+/// while let Some(player) = batch.next().await {
+///     match player.result {
+///         Ok(stats) => println!("{}: level {}", player.player, stats.progression.level),
+///         Err(e) => println!("{}: {}", player.player, e),
+///     }
+///     println!("{}/{}", batch.completed(), batch.total());
+/// }
+/// # }
+/// ```
+///
+/// [`Client::with_max_in_flight`]: struct.Client.html#method.with_max_in_flight
+pub struct Batch<T> {
+    lookups: BoxStream<PlayerResult<T>>,
+    total: usize,
+    completed: usize,
+}
+
+impl<T> Batch<T> {
+    pub(crate) fn new(lookups: BoxStream<PlayerResult<T>>, total: usize) -> Self {
+        Self {
+            lookups,
+            total,
+            completed: 0,
+        }
+    }
+
+    /// The number of players in the batch.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The number of players whose lookup completed.
+    pub fn completed(&self) -> usize {
+        self.completed
+    }
+
+    /// The number of players whose lookup is pending.
+    pub fn remaining(&self) -> usize {
+        self.total - self.completed
+    }
+}
+
+impl<T> Stream for Batch<T> {
+    type Item = PlayerResult<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.lookups.as_mut().poll_next(cx);
+        if let Poll::Ready(Some(_)) = poll {
+            self.completed += 1;
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl<T> std::fmt::Debug for Batch<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Batch")
+            .field("total", &self.total)
+            .field("completed", &self.completed)
+            .finish()
+    }
+}
@@ -1,133 +1,439 @@
+use super::batch::{Batch, PlayerResult};
 use super::http::RouteBuilder;
-use super::model::{GenericStats, OperatorStats, SeasonalStats, WeaponCategoryStats, WeaponStats};
-use super::Kind;
+use super::model::{
+    GenericStats, OperatorStats, PlayerProfile, SeasonalStats, Stats, WeaponCategoryStats,
+    WeaponStats,
+};
+use super::{Kind, Lenient, PlayerId, Resolver};
+use crate::http::{Endpoint, Priority, Request};
 use crate::internals::Rc;
 use crate::{Error, Http, Platform};
-use reqwest::Response;
+use bytes::Bytes;
+use futures::{stream, StreamExt};
+use reqwest::StatusCode;
+
+const DEFAULT_MAX_IN_FLIGHT: usize = 4;
 
 /// Client for the stats endpoint.
 #[derive(Clone, Debug)]
 pub struct Client {
     http: Rc<Http>,
+    priority: Priority,
+    force_refresh: bool,
+    max_in_flight: usize,
+    resolver: Rc<Resolver>,
 }
 
 impl Client {
-    pub(crate) fn new(http: Rc<Http>) -> Self {
-        Self { http }
+    pub(crate) fn new(http: Rc<Http>, resolver: Rc<Resolver>) -> Self {
+        Self {
+            http,
+            priority: Priority::default(),
+            force_refresh: false,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            resolver,
+        }
+    }
+
+    /// Returns a client whose requests have the [`Priority`] while waiting for the ratelimit.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use r6stats_client::{Client, Platform};
+    /// use r6stats_client::http::Priority;
+    ///
+    /// # async fn run(client: Client) -> Result<(), r6stats_client::Error> {
+    /// let stats = client
+    ///     .stats()
+    ///     .with_priority(Priority::High)
+    ///     .seasonal("pengu.g2", Platform::Pc)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Priority`]: ../http/enum.Priority.html
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client whose requests skip the response cache.
+    ///
+    /// The responses are still cached for later requests. Has no effect if caching is disabled.
+    pub fn force_refresh(&self) -> Self {
+        Self {
+            force_refresh: true,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client whose batch lookups send up to `max_in_flight` requests at a time.
+    ///
+    /// Defaults to `4`. Values below `1` are treated as `1`.
+    pub fn with_max_in_flight(&self, max_in_flight: usize) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            ..self.clone()
+        }
+    }
+
+    /// The [`Priority`] of the requests of this client.
+    ///
+    /// [`Priority`]: ../http/enum.Priority.html
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// The maximum number of concurrent requests of a batch lookup.
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    /// The [`Resolver`] looking up the usernames of players given by their Ubisoft ID.
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use r6stats_client::{Client, Platform};
+    /// use r6stats_client::stats::PlayerId;
+    ///
+    /// # async fn run(client: Client) -> Result<(), r6stats_client::Error> {
+    /// let id = "e0af7ebb-3b8c-4d0e-b2ec-dd4c07bd2b7d";
+    /// client.stats().resolver().insert(id, "pengu.g2");
+    ///
+    /// let stats = client
+    ///     .stats()
+    ///     .generic(PlayerId::UbisoftId(id.to_string()), Platform::Pc)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Resolver`]: struct.Resolver.html
+    pub fn resolver(&self) -> &Resolver {
+        &self.resolver
     }
 
     /// Returns the generic stats of a player.
     ///
     /// # Args
     ///
-    /// - `username` - The username of the player
+    /// - `player` - The [`PlayerId`] of the player
     /// - `platform` - The [`Platform`] of the player
     ///
+    /// [`PlayerId`]: enum.PlayerId.html
     /// [`Platform`]: ../../platform/enum.Platform.html
     pub async fn generic(
         &self,
-        username: impl AsRef<str>,
+        player: impl Into<PlayerId>,
         platform: Platform,
     ) -> Result<GenericStats, Error> {
-        let response = self
-            .request(username.as_ref(), platform, Kind::Generic)
-            .await?;
-        let bytes = response.bytes().await?;
-        Ok(serde_json::from_slice(&bytes)?)
+        self.get(&player.into(), platform).await
     }
 
     /// Returns the generic stats of a player.
     ///
     /// # Args
     ///
-    /// - `username` - The username of the player
+    /// - `player` - The [`PlayerId`] of the player
     /// - `platform` - The [`Platform`] of the player
     ///
+    /// [`PlayerId`]: enum.PlayerId.html
     /// [`Platform`]: ../../platform/enum.Platform.html
     pub async fn seasonal(
         &self,
-        username: impl AsRef<str>,
+        player: impl Into<PlayerId>,
         platform: Platform,
     ) -> Result<SeasonalStats, Error> {
-        let response = self
-            .request(username.as_ref(), platform, Kind::Seasonal)
-            .await?;
-        let bytes = response.bytes().await?;
-        Ok(serde_json::from_slice(&bytes)?)
+        self.get(&player.into(), platform).await
     }
 
     /// Returns the operator stats of a player.
     ///
     /// # Args
     ///
-    /// - `username` - The username of the player
+    /// - `player` - The [`PlayerId`] of the player
     /// - `platform` - The [`Platform`] of the player
     ///
+    /// [`PlayerId`]: enum.PlayerId.html
     /// [`Platform`]: ../../platform/enum.Platform.html
     pub async fn operators(
         &self,
-        username: impl AsRef<str>,
+        player: impl Into<PlayerId>,
         platform: Platform,
     ) -> Result<OperatorStats, Error> {
-        let response = self
-            .request(username.as_ref(), platform, Kind::Operators)
-            .await?;
-        let bytes = response.bytes().await?;
-        Ok(serde_json::from_slice(&bytes)?)
+        self.get(&player.into(), platform).await
     }
 
     /// Returns the weapon-category stats of a player.
     ///
     /// # Args
     ///
-    /// - `username` - The username of the player
+    /// - `player` - The [`PlayerId`] of the player
     /// - `platform` - The [`Platform`] of the player
     ///
+    /// [`PlayerId`]: enum.PlayerId.html
     /// [`Platform`]: ../../platform/enum.Platform.html
     pub async fn weapon_categories(
         &self,
-        username: impl AsRef<str>,
+        player: impl Into<PlayerId>,
         platform: Platform,
     ) -> Result<WeaponCategoryStats, Error> {
-        let response = self
-            .request(username.as_ref(), platform, Kind::WeaponCategories)
-            .await?;
-        let bytes = response.bytes().await?;
-        Ok(serde_json::from_slice(&bytes)?)
+        self.get(&player.into(), platform).await
     }
 
     /// Returns the weapon stats of a player.
     ///
     /// # Args
     ///
-    /// - `username` - The username of the player
+    /// - `player` - The [`PlayerId`] of the player
     /// - `platform` - The [`Platform`] of the player
     ///
+    /// [`PlayerId`]: enum.PlayerId.html
     /// [`Platform`]: ../../platform/enum.Platform.html
     pub async fn weapons(
         &self,
-        username: impl AsRef<str>,
+        player: impl Into<PlayerId>,
         platform: Platform,
     ) -> Result<WeaponStats, Error> {
-        let response = self
-            .request(username.as_ref(), platform, Kind::Weapons)
-            .await?;
-        let bytes = response.bytes().await?;
-        Ok(serde_json::from_slice(&bytes)?)
+        self.get(&player.into(), platform).await
+    }
+
+    /// Returns the stats of all [`Kind`]s of a player.
+    ///
+    /// The requests are sent concurrently. If some of them fail, the stats received are still
+    /// returned, see [`PlayerProfile::errors`]. Fails only if all requests fail.
+    ///
+    /// # Args
+    ///
+    /// - `player` - The [`PlayerId`] of the player
+    /// - `platform` - The [`Platform`] of the player
+    ///
+    /// [`Kind`]: ../enum.Kind.html
+    /// [`PlayerProfile::errors`]: ../model/profile/struct.PlayerProfile.html#structfield.errors
+    /// [`PlayerId`]: enum.PlayerId.html
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub async fn all(
+        &self,
+        player: impl Into<PlayerId>,
+        platform: Platform,
+    ) -> Result<PlayerProfile, Error> {
+        let player = player.into();
+
+        let (generic, seasonal, operators, weapon_categories, weapons) = tokio::join!(
+            self.generic(player.clone(), platform),
+            self.seasonal(player.clone(), platform),
+            self.operators(player.clone(), platform),
+            self.weapon_categories(player.clone(), platform),
+            self.weapons(player, platform),
+        );
+
+        PlayerProfile::merge(generic, seasonal, operators, weapon_categories, weapons)
+    }
+
+    /// Returns the generic stats of many players, see [`Batch`].
+    ///
+    /// # Args
+    ///
+    /// - `players` - The [`PlayerId`]s and [`Platform`]s of the players
+    ///
+    /// [`Batch`]: struct.Batch.html
+    /// [`PlayerId`]: enum.PlayerId.html
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub fn generic_many<I, P>(&self, players: I) -> Batch<GenericStats>
+    where
+        I: IntoIterator<Item = (P, Platform)>,
+        P: Into<PlayerId>,
+    {
+        self.many(players)
+    }
+
+    /// Returns the seasonal stats of many players, see [`Batch`].
+    ///
+    /// # Args
+    ///
+    /// - `players` - The [`PlayerId`]s and [`Platform`]s of the players
+    ///
+    /// [`Batch`]: struct.Batch.html
+    /// [`PlayerId`]: enum.PlayerId.html
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub fn seasonal_many<I, P>(&self, players: I) -> Batch<SeasonalStats>
+    where
+        I: IntoIterator<Item = (P, Platform)>,
+        P: Into<PlayerId>,
+    {
+        self.many(players)
+    }
+
+    /// Returns the operator stats of many players, see [`Batch`].
+    ///
+    /// # Args
+    ///
+    /// - `players` - The [`PlayerId`]s and [`Platform`]s of the players
+    ///
+    /// [`Batch`]: struct.Batch.html
+    /// [`PlayerId`]: enum.PlayerId.html
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub fn operators_many<I, P>(&self, players: I) -> Batch<OperatorStats>
+    where
+        I: IntoIterator<Item = (P, Platform)>,
+        P: Into<PlayerId>,
+    {
+        self.many(players)
+    }
+
+    /// Returns the weapon-category stats of many players, see [`Batch`].
+    ///
+    /// # Args
+    ///
+    /// - `players` - The [`PlayerId`]s and [`Platform`]s of the players
+    ///
+    /// [`Batch`]: struct.Batch.html
+    /// [`PlayerId`]: enum.PlayerId.html
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub fn weapon_categories_many<I, P>(&self, players: I) -> Batch<WeaponCategoryStats>
+    where
+        I: IntoIterator<Item = (P, Platform)>,
+        P: Into<PlayerId>,
+    {
+        self.many(players)
+    }
+
+    /// Returns the weapon stats of many players, see [`Batch`].
+    ///
+    /// # Args
+    ///
+    /// - `players` - The [`PlayerId`]s and [`Platform`]s of the players
+    ///
+    /// [`Batch`]: struct.Batch.html
+    /// [`PlayerId`]: enum.PlayerId.html
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub fn weapons_many<I, P>(&self, players: I) -> Batch<WeaponStats>
+    where
+        I: IntoIterator<Item = (P, Platform)>,
+        P: Into<PlayerId>,
+    {
+        self.many(players)
+    }
+
+    /// Returns the stats of a player, decoded leniently, see [`Lenient`].
+    ///
+    /// The [`Kind`] of the stats is given by `T`. Use this if the API changed the type of some
+    /// values, which fails the other methods with [`Error::JsonError`].
+    ///
+    /// ## Example
+    /// ```rust,no_run
+    /// # use r6stats_client::{Client, Platform};
+    /// use r6stats_client::stats::model::GenericStats;
+    ///
+    /// # async fn run(client: Client) -> Result<(), r6stats_client::Error> {
+    /// let stats = client
+    ///     .stats()
+    ///     .lenient::<GenericStats>("pengu.g2", Platform::Pc)
+    ///     .await?;
+    ///
+    /// for issue in &stats.issues {
+    ///     println!("{}", issue);
+    /// }
+    /// println!("level {}", stats.value.progression.level);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Args
+    ///
+    /// - `player` - The [`PlayerId`] of the player
+    /// - `platform` - The [`Platform`] of the player
+    ///
+    /// [`Lenient`]: struct.Lenient.html
+    /// [`Kind`]: ../enum.Kind.html
+    /// [`Error::JsonError`]: ../../error/enum.Error.html#variant.JsonError
+    /// [`PlayerId`]: enum.PlayerId.html
+    /// [`Platform`]: ../../platform/enum.Platform.html
+    pub async fn lenient<T: Stats>(
+        &self,
+        player: impl Into<PlayerId>,
+        platform: Platform,
+    ) -> Result<Lenient<T>, Error> {
+        let bytes = self.request(&player.into(), platform, T::KIND).await?;
+        let stats = Lenient::<T>::from_slice(&bytes)?;
+
+        self.resolver
+            .insert(stats.value.ubisoft_id(), stats.value.username());
+        Ok(stats)
+    }
+
+    fn many<T, I, P>(&self, players: I) -> Batch<T>
+    where
+        T: Stats + 'static,
+        I: IntoIterator<Item = (P, Platform)>,
+        P: Into<PlayerId>,
+    {
+        let players: Vec<_> = players
+            .into_iter()
+            .map(|(player, platform)| (player.into(), platform))
+            .collect();
+        let total = players.len();
+
+        let client = self.clone();
+        let lookups = stream::iter(players)
+            .map(move |(player, platform)| {
+                let client = client.clone();
+                async move {
+                    let result = client.get(&player, platform).await;
+                    PlayerResult {
+                        player,
+                        platform,
+                        result,
+                    }
+                }
+            })
+            .buffer_unordered(self.max_in_flight);
+
+        Batch::new(Box::pin(lookups), total)
+    }
+
+    async fn get<T: Stats>(&self, player: &PlayerId, platform: Platform) -> Result<T, Error> {
+        let bytes = self.request(player, platform, T::KIND).await?;
+        let stats: T = serde_json::from_slice(&bytes)?;
+
+        self.resolver.insert(stats.ubisoft_id(), stats.username());
+        Ok(stats)
     }
 
     async fn request(
         &self,
-        username: &str,
+        player: &PlayerId,
         platform: Platform,
         kind: Kind,
-    ) -> Result<Response, Error> {
+    ) -> Result<Bytes, Error> {
         let route = RouteBuilder::new()
-            .username(username)
+            .player(player.clone())
             .platform(platform)
             .kind(kind)
-            .build()?;
-        let path = route.path();
-        self.http.request(&path).await
+            .build(&self.resolver)?;
+
+        let request = Request {
+            url: route.url(self.http.base_url()),
+            endpoint: Endpoint::Stats(kind),
+            priority: self.priority,
+            force_refresh: self.force_refresh,
+        };
+
+        match self.http.request(&request).await {
+            Err(Error::HttpError(err))
+                if err.unsuccessful_request() == Some(StatusCode::NOT_FOUND) =>
+            {
+                Err(Error::PlayerNotFound {
+                    username: route.username().to_string(),
+                    platform,
+                    message: err.api_error().map(|e| e.message.clone()),
+                    source: Box::new(err),
+                })
+            }
+            result => result,
+        }
     }
 }
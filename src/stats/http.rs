@@ -1,11 +1,12 @@
-use super::Kind;
-use crate::internals::utils::check_username;
+use super::{Kind, PlayerId, Resolver};
+use crate::internals::utils::{check_username, join_segments};
 use crate::platform::Platform;
 use crate::Error;
+use reqwest::Url;
 
 #[derive(Clone, Debug, Default)]
 pub(super) struct RouteBuilder {
-    username: Option<String>,
+    player: Option<PlayerId>,
     platform: Option<Platform>,
     kind: Option<Kind>,
 }
@@ -15,8 +16,8 @@ impl RouteBuilder {
         Self::default()
     }
 
-    pub fn username(mut self, username: &str) -> Self {
-        self.username = Some(username.to_string());
+    pub fn player(mut self, player: PlayerId) -> Self {
+        self.player = Some(player);
         self
     }
 
@@ -30,15 +31,22 @@ impl RouteBuilder {
         self
     }
 
-    pub fn build(self) -> Result<RouteInfo, Error> {
-        let username = self
-            .username
-            .expect("Error creating route: Username missing.");
+    /// Returns the route, looking up the username of players given by their Ubisoft ID.
+    pub fn build(self, resolver: &Resolver) -> Result<RouteInfo, Error> {
+        let player = self.player.expect("Error creating route: Player missing.");
         let platform = self
             .platform
             .expect("Error creating route: Platform missing.");
         let kind = self.kind.expect("Error creating route: Kind missing.");
 
+        let username = match player {
+            PlayerId::Username(username) => username,
+            PlayerId::UbisoftId(id) => match resolver.username(&id) {
+                Some(username) => username,
+                None => return Err(Error::UnknownUbisoftId(id)),
+            },
+        };
+
         check_username(&username)?;
 
         let route = RouteInfo {
@@ -58,19 +66,29 @@ pub(super) struct RouteInfo {
 }
 
 impl RouteInfo {
-    pub fn path(&self) -> String {
-        format!(
-            api!("/stats/{u}/{p}/{k}"),
-            u = &self.username,
-            p = self.platform.as_str(),
-            k = self.kind.as_str()
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn url(&self, base: &Url) -> Url {
+        join_segments(
+            base,
+            &[
+                "stats",
+                &self.username,
+                self.platform.as_str(),
+                self.kind.as_str(),
+            ],
         )
     }
 }
+
 #[cfg(test)]
 mod tests {
-    use super::{Kind, RouteBuilder};
-    use crate::Platform;
+    use super::{Kind, PlayerId, Resolver, RouteBuilder};
+    use crate::http::DEFAULT_BASE_URL;
+    use crate::{Error, Platform};
+    use reqwest::Url;
 
     #[test]
     fn test_routing() {
@@ -79,12 +97,44 @@ mod tests {
         let kind = Kind::Generic;
 
         let route = RouteBuilder::new()
-            .username(username)
+            .player(username.into())
             .platform(platform)
             .kind(kind)
-            .build()
+            .build(&Resolver::default())
             .unwrap();
 
-        assert_eq!(route.path(), api!("/stats/pengu.g2/pc/generic"));
+        let base = Url::parse(DEFAULT_BASE_URL).unwrap();
+        assert_eq!(
+            route.url(&base).as_str(),
+            "https://api2.r6stats.com/public-api/stats/pengu.g2/pc/generic"
+        );
+
+        let base = Url::parse("http://127.0.0.1:8080/mock/").unwrap();
+        assert_eq!(
+            route.url(&base).as_str(),
+            "http://127.0.0.1:8080/mock/stats/pengu.g2/pc/generic"
+        );
+    }
+
+    #[test]
+    fn test_ubisoft_id() {
+        let resolver = Resolver::default();
+        let id = "a1b2c3";
+        let route = || {
+            RouteBuilder::new()
+                .player(PlayerId::UbisoftId(id.to_string()))
+                .platform(Platform::Pc)
+                .kind(Kind::Generic)
+                .build(&resolver)
+        };
+
+        assert!(matches!(route(), Err(Error::UnknownUbisoftId(_))));
+
+        resolver.insert(id, "pengu.g2");
+        let base = Url::parse(DEFAULT_BASE_URL).unwrap();
+        assert_eq!(
+            route().unwrap().url(&base).as_str(),
+            "https://api2.r6stats.com/public-api/stats/pengu.g2/pc/generic"
+        );
     }
 }
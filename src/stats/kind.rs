@@ -1,7 +1,7 @@
 /// Type of stats to request.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[non_exhaustive]
-pub(super) enum Kind {
+pub enum Kind {
     Generic,
     Seasonal,
     Operators,
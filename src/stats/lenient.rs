@@ -0,0 +1,64 @@
+//! Module for decoding stats which don't match their model.
+
+mod de;
+
+use crate::Error;
+use serde::de::DeserializeOwned;
+use std::fmt::{self, Display, Formatter};
+
+/// Stats decoded leniently, along with the issues found in the response.
+///
+/// Values of unexpected types are coerced where possible: numbers sent as strings are parsed,
+/// integers out of range are clamped, and floats sent for integers are rounded. Values which
+/// still can't be decoded are ignored if they are optional. Every coercion is recorded as an
+/// [`Issue`].
+///
+/// [`Issue`]: struct.Issue.html
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Lenient<T> {
+    /// The best-effort stats.
+    pub value: T,
+    /// The values which didn't match the model.
+    pub issues: Vec<Issue>,
+}
+
+impl<T: DeserializeOwned> Lenient<T> {
+    /// Decodes `T` leniently from the body of a response.
+    ///
+    /// Fails if the body isn't JSON, or a required value can't be decoded.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        let value = serde_json::from_slice(bytes)?;
+        let (value, issues) = de::from_value(value)?;
+
+        Ok(Self { value, issues })
+    }
+}
+
+impl<T> Lenient<T> {
+    /// Returns true if the response matched the model.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the stats, discarding the issues.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+/// A value of a response which didn't match its model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Issue {
+    /// The path of the value, like `stats.general.wins` or `operators[3].kd`.
+    pub path: String,
+    /// What was wrong with the value, and how it was decoded.
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
@@ -0,0 +1,430 @@
+//! A deserializer for JSON values, coercing values of unexpected types.
+
+use super::Issue;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde_json::{map, Error, Value};
+use std::cell::RefCell;
+use std::fmt::{self, Display, Formatter};
+use std::slice;
+
+/// Deserializes `T`, ignoring values which can't be decoded until it succeeds.
+///
+/// A value which fails is replaced with `null`, so it's decoded as `None` if it's optional.
+/// Gives up once a `null` value fails.
+pub(super) fn from_value<T: DeserializeOwned>(mut value: Value) -> Result<(T, Vec<Issue>), Error> {
+    let mut ignored = Vec::new();
+
+    loop {
+        let state = State::default();
+        let err = match T::deserialize(Lenient::new(&value, Path::default(), &state)) {
+            Ok(t) => {
+                ignored.extend(state.issues.into_inner());
+                return Ok((t, ignored));
+            }
+            Err(err) => err,
+        };
+
+        match state.failed.into_inner() {
+            Some(path) if path.set_null(&mut value) => ignored.push(Issue {
+                path: path.to_string(),
+                message: format!("{}, ignored the value", err),
+            }),
+            _ => return Err(err),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    issues: RefCell<Vec<Issue>>,
+    /// The innermost value which failed.
+    failed: RefCell<Option<Path>>,
+}
+
+impl State {
+    fn record<T>(&self, path: Path, result: Result<T, Error>) -> Result<T, Error> {
+        if result.is_err() {
+            self.failed.borrow_mut().get_or_insert(path);
+        }
+        result
+    }
+}
+
+#[derive(Clone, Default)]
+struct Path(Vec<Segment>);
+
+#[derive(Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl Path {
+    fn join(&self, segment: Segment) -> Self {
+        let mut path = self.clone();
+        path.0.push(segment);
+        path
+    }
+
+    /// Replaces the value at the path with `null`.
+    ///
+    /// Returns false if the value doesn't exist or is `null` already.
+    fn set_null(&self, mut value: &mut Value) -> bool {
+        for segment in &self.0 {
+            let next = match (segment, value) {
+                (Segment::Key(key), Value::Object(map)) => map.get_mut(key),
+                (Segment::Index(index), Value::Array(vec)) => vec.get_mut(*index),
+                _ => None,
+            };
+            value = match next {
+                Some(next) => next,
+                None => return false,
+            };
+        }
+
+        if value.is_null() {
+            return false;
+        }
+        *value = Value::Null;
+        true
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                Segment::Key(key) => write!(f, ".{}", key)?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Lenient<'a> {
+    value: &'a Value,
+    path: Path,
+    state: &'a State,
+}
+
+impl<'a> Lenient<'a> {
+    fn new(value: &'a Value, path: Path, state: &'a State) -> Self {
+        Self { value, path, state }
+    }
+
+    fn warn(&self, message: String) {
+        self.state.issues.borrow_mut().push(Issue {
+            path: self.path.to_string(),
+            message,
+        });
+    }
+
+    /// Returns the value as an integer within `min..=max`, coercing it if needed.
+    fn integer(&self, min: i128, max: i128, ty: &str) -> Option<i128> {
+        let n = match self.value {
+            Value::Number(n) => match (n.as_u64(), n.as_i64(), n.as_f64()) {
+                (Some(n), _, _) => i128::from(n),
+                (_, Some(n), _) => i128::from(n),
+                (_, _, Some(f)) if f.is_finite() => {
+                    self.warn(format!("expected {}, rounded the float {}", ty, f));
+                    f.round() as i128
+                }
+                _ => return None,
+            },
+            Value::String(s) => {
+                let n = parse_integer(s)?;
+                self.warn(format!("expected {}, parsed the string {:?}", ty, s));
+                n
+            }
+            _ => return None,
+        };
+
+        if n < min || n > max {
+            let clamped = n.clamp(min, max);
+            self.warn(format!(
+                "{} is out of range for {}, clamped to {}",
+                n, ty, clamped
+            ));
+            return Some(clamped);
+        }
+        Some(n)
+    }
+
+    /// Returns the value as a float, coercing it if needed.
+    fn float(&self, ty: &str) -> Option<f64> {
+        match self.value {
+            Value::String(s) => {
+                let f = s.trim().parse().ok()?;
+                self.warn(format!("expected {}, parsed the string {:?}", ty, s));
+                Some(f)
+            }
+            _ => None,
+        }
+    }
+
+    fn visit_object<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Object(map) => visitor.visit_map(Map {
+                entries: map.iter(),
+                value: None,
+                path: self.path,
+                state: self.state,
+            }),
+            value => value.deserialize_map(visitor),
+        }
+    }
+
+    fn visit_array<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Array(vec) => visitor.visit_seq(Seq {
+                elements: vec.iter().enumerate(),
+                path: self.path,
+                state: self.state,
+            }),
+            value => value.deserialize_seq(visitor),
+        }
+    }
+}
+
+fn parse_integer(s: &str) -> Option<i128> {
+    let s = s.trim();
+    s.parse().ok().or_else(|| {
+        s.parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(|f| f.round() as i128)
+    })
+}
+
+macro_rules! deserialize_integer {
+    ($($method:ident => $ty:ident, $visit:ident;)*) => {
+        $(
+            fn $method<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+                let range = (i128::from($ty::MIN), i128::from($ty::MAX));
+                match self.integer(range.0, range.1, stringify!($ty)) {
+                    Some(n) => visitor.$visit(n as $ty),
+                    None => self.value.$method(visitor),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_float {
+    ($($method:ident => $ty:ident;)*) => {
+        $(
+            fn $method<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.float(stringify!($ty)) {
+                    Some(f) => visitor.visit_f64(f),
+                    None => self.value.$method(visitor),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident$(($($arg:ident: $ty:ty),*))?;)*) => {
+        $(
+            fn $method<V: Visitor<'a>>(
+                self,
+                $($($arg: $ty,)*)?
+                visitor: V,
+            ) -> Result<V::Value, Error> {
+                self.value.$method($($($arg,)*)? visitor)
+            }
+        )*
+    };
+}
+
+impl<'a> Deserializer<'a> for Lenient<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Object(_) => self.visit_object(visitor),
+            Value::Array(_) => self.visit_array(visitor),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_integer! {
+        deserialize_u8 => u8, visit_u8;
+        deserialize_u16 => u16, visit_u16;
+        deserialize_u32 => u32, visit_u32;
+        deserialize_u64 => u64, visit_u64;
+        deserialize_i8 => i8, visit_i8;
+        deserialize_i16 => i16, visit_i16;
+        deserialize_i32 => i32, visit_i32;
+        deserialize_i64 => i64, visit_i64;
+    }
+
+    deserialize_float! {
+        deserialize_f32 => f32;
+        deserialize_f64 => f64;
+    }
+
+    fn deserialize_bool<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        let b = match self.value {
+            Value::String(s) => s.trim().parse().ok(),
+            Value::Number(n) => match n.as_u64() {
+                Some(0) => Some(false),
+                Some(1) => Some(true),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match b {
+            Some(b) => {
+                self.warn(format!("expected bool, converted {}", self.value));
+                visitor.visit_bool(b)
+            }
+            None => self.value.deserialize_bool(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Number(_) | Value::Bool(_) => {
+                self.warn(format!("expected a string, converted {}", self.value));
+                visitor.visit_string(self.value.to_string())
+            }
+            value => value.deserialize_str(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'a>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.visit_array(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.visit_object(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.visit_object(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_value! {
+        deserialize_char;
+        deserialize_bytes;
+        deserialize_byte_buf;
+        deserialize_unit;
+        deserialize_identifier;
+        deserialize_unit_struct(name: &'static str);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+    }
+}
+
+struct Map<'a> {
+    entries: map::Iter<'a>,
+    value: Option<(&'a str, &'a Value)>,
+    path: Path,
+    state: &'a State,
+}
+
+impl<'a> MapAccess<'a> for Map<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'a>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let (key, value) = match self.entries.next() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        self.value = Some((key, value));
+
+        let path = self.path.join(Segment::Key(key.clone()));
+        let key = seed.deserialize(key.as_str().into_deserializer());
+        self.state.record(path, key).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'a>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, value) = self.value.take().expect("value requested before key");
+
+        let path = self.path.join(Segment::Key(key.to_string()));
+        let value = seed.deserialize(Lenient::new(value, path.clone(), self.state));
+        self.state.record(path, value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct Seq<'a> {
+    elements: std::iter::Enumerate<slice::Iter<'a, Value>>,
+    path: Path,
+    state: &'a State,
+}
+
+impl<'a> SeqAccess<'a> for Seq<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'a>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        let (index, value) = match self.elements.next() {
+            Some(element) => element,
+            None => return Ok(None),
+        };
+
+        let path = self.path.join(Segment::Index(index));
+        let value = seed.deserialize(Lenient::new(value, path.clone(), self.state));
+        self.state.record(path, value).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
@@ -1,13 +1,66 @@
 //! Models for stats endpoint.
+//!
+//! Models compare their floats by their bits, so `NaN` equals itself and `0.0` differs from
+//! `-0.0`, which keeps the comparison an equivalence. Use [`Fingerprint`] to detect changes
+//! cheaply.
+//!
+//! [`Fingerprint`]: ../../struct.Fingerprint.html
 
 pub mod generic;
 pub mod operators;
+pub mod profile;
 pub mod seasonal;
 pub mod weapon_categories;
 pub mod weapons;
 
 pub use self::generic::GenericStats;
 pub use self::operators::OperatorStats;
+pub use self::profile::PlayerProfile;
 pub use self::seasonal::SeasonalStats;
 pub use self::weapon_categories::WeaponCategoryStats;
 pub use self::weapons::WeaponStats;
+
+use super::Kind;
+use serde::de::DeserializeOwned;
+
+/// The stats of a [`Kind`], naming the player they belong to.
+///
+/// [`Kind`]: ../enum.Kind.html
+pub trait Stats: DeserializeOwned {
+    /// The [`Kind`] of the stats.
+    ///
+    /// [`Kind`]: ../enum.Kind.html
+    const KIND: Kind;
+
+    /// The username of the player.
+    fn username(&self) -> &str;
+
+    /// The Ubisoft ID of the player.
+    fn ubisoft_id(&self) -> &str;
+}
+
+macro_rules! impl_stats {
+    ($($ty:ty => $kind:expr),*) => {
+        $(
+            impl Stats for $ty {
+                const KIND: Kind = $kind;
+
+                fn username(&self) -> &str {
+                    &self.username
+                }
+
+                fn ubisoft_id(&self) -> &str {
+                    &self.ubisoft_id
+                }
+            }
+        )*
+    };
+}
+
+impl_stats!(
+    GenericStats => Kind::Generic,
+    SeasonalStats => Kind::Seasonal,
+    OperatorStats => Kind::Operators,
+    WeaponCategoryStats => Kind::WeaponCategories,
+    WeaponStats => Kind::Weapons
+);
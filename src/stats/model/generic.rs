@@ -4,12 +4,13 @@ mod queue_mode;
 
 pub use self::queue_mode::QueueMode;
 
+use crate::internals::impl_bitwise_eq;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Deserialized generic stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct GenericStats {
     pub username: String,
@@ -25,7 +26,7 @@ pub struct GenericStats {
 }
 
 /// Deserialized aliases.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct Alias {
     pub username: String,
@@ -33,16 +34,23 @@ pub struct Alias {
 }
 
 /// Deserialized progression.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct Progression {
     pub level: u16,
     pub lootbox_probability: f32,
-    pub total_xp: u32,
+    pub total_xp: u64,
 }
 
+impl_bitwise_eq!(Progression {
+    level,
+    total_xp,
+} floats {
+    lootbox_probability,
+});
+
 /// Deserialized stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct StatsInfo {
     pub general: GeneralStatsInfo,
@@ -52,58 +60,117 @@ pub struct StatsInfo {
 }
 
 /// Deserialized general stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct GeneralStatsInfo {
     pub assists: u32,
-    pub barricades_deployed: u16,
-    pub blind_kills: u16,
+    pub barricades_deployed: u32,
+    pub blind_kills: u32,
     pub bullets_fired: u64,
     pub bullets_hit: u64,
     pub dbnos: u32,
     pub deaths: u32,
-    /// The travelled distance.
+    /// The travelled distance, as sent by the API.
     ///
     /// Note, that this value can overflow (more than once)
     /// because the player travelled more than ubisoft expected.
-    pub distance_travelled: i32,
-    pub draws: u16,
+    /// See [`distance_travelled_corrected`] for the corrected value.
+    ///
+    /// [`distance_travelled_corrected`]: #method.distance_travelled_corrected
+    pub distance_travelled: i64,
+    pub draws: u32,
     pub gadgets_destroyed: u32,
-    pub games_played: u16,
+    pub games_played: u32,
     pub headshots: u32,
     pub kd: f32,
     pub kills: u32,
     pub losses: u32,
-    pub melee_kills: u16,
-    pub penetration_kills: u16,
+    pub melee_kills: u32,
+    pub penetration_kills: u32,
     pub playtime: u64,
-    pub rappel_breaches: u16,
-    pub reinforcements_deployed: u16,
-    pub revives: u16,
-    pub suicides: u16,
-    pub wins: u16,
+    pub rappel_breaches: u32,
+    pub reinforcements_deployed: u32,
+    pub revives: u32,
+    pub suicides: u32,
+    pub wins: u32,
     pub wl: f32,
 }
 
+impl_bitwise_eq!(GeneralStatsInfo {
+    assists,
+    barricades_deployed,
+    blind_kills,
+    bullets_fired,
+    bullets_hit,
+    dbnos,
+    deaths,
+    distance_travelled,
+    draws,
+    gadgets_destroyed,
+    games_played,
+    headshots,
+    kills,
+    losses,
+    melee_kills,
+    penetration_kills,
+    playtime,
+    rappel_breaches,
+    reinforcements_deployed,
+    revives,
+    suicides,
+    wins,
+} floats {
+    kd,
+    wl,
+});
+
+impl GeneralStatsInfo {
+    /// Returns the travelled distance, corrected for one overflow of the 32-bit counter.
+    ///
+    /// A value which overflowed more than once is still too small.
+    pub fn distance_travelled_corrected(&self) -> u64 {
+        let distance = self.distance_travelled;
+        if distance < 0 {
+            // The counter is a signed 32-bit integer
+            (distance + (1 << 32)).max(0) as u64
+        } else {
+            distance as u64
+        }
+    }
+}
+
 /// Deserialized info for the [`QueueMode`].
 ///
 /// [`QueueMode`]: enum.QueueMode.html
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct QueueInfo {
     pub deaths: u32,
-    pub draws: u16,
-    pub games_played: u16,
+    pub draws: u32,
+    pub games_played: u32,
     pub kd: f32,
     pub kills: u32,
-    pub losses: u16,
+    pub losses: u32,
     pub playtime: u64,
-    pub wins: u16,
+    pub wins: u32,
     pub wl: f32,
 }
 
+impl_bitwise_eq!(QueueInfo {
+    deaths,
+    draws,
+    games_played,
+    kills,
+    losses,
+    playtime,
+    wins,
+} floats {
+    kd,
+    wl,
+});
+
 /// Deserialized gamemodes.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct GamemodeInfo {
     pub bomb: BombInfo,
     pub secure_area: SecureAreaInfo,
@@ -111,47 +178,81 @@ pub struct GamemodeInfo {
 }
 
 /// Deserialized info for the bomb gamemode.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct BombInfo {
-    pub best_score: u16,
-    pub games_played: u16,
-    pub losses: u16,
+    pub best_score: u32,
+    pub games_played: u32,
+    pub losses: u32,
     pub playtime: u64,
-    pub wins: u16,
+    pub wins: u32,
     pub wl: f32,
 }
 
+impl_bitwise_eq!(BombInfo {
+    best_score,
+    games_played,
+    losses,
+    playtime,
+    wins,
+} floats {
+    wl,
+});
+
 /// Deserialized info for the secure area gamemode.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct SecureAreaInfo {
-    pub best_score: u16,
-    pub games_played: u16,
+    pub best_score: u32,
+    pub games_played: u32,
     pub kills_as_attacker_in_objective: u32,
     pub kills_as_defender_in_objective: u32,
-    pub losses: u16,
+    pub losses: u32,
     pub playtime: u64,
-    pub times_objective_secured: u16,
-    pub wins: u16,
+    pub times_objective_secured: u32,
+    pub wins: u32,
     pub wl: f32,
 }
 
+impl_bitwise_eq!(SecureAreaInfo {
+    best_score,
+    games_played,
+    kills_as_attacker_in_objective,
+    kills_as_defender_in_objective,
+    losses,
+    playtime,
+    times_objective_secured,
+    wins,
+} floats {
+    wl,
+});
+
 /// Deserialized info for the hostage gamemode.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct HostageInfo {
-    pub best_score: u16,
-    pub games_played: u16,
-    pub losses: u16,
+    pub best_score: u32,
+    pub games_played: u32,
+    pub losses: u32,
     pub playtime: u64,
-    pub extractions_denied: u16,
-    pub wins: u16,
+    pub extractions_denied: u32,
+    pub wins: u32,
     pub wl: f32,
 }
 
+impl_bitwise_eq!(HostageInfo {
+    best_score,
+    games_played,
+    losses,
+    playtime,
+    extractions_denied,
+    wins,
+} floats {
+    wl,
+});
+
 /// Deserialized timestamps.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct Timestamps {
     pub created: DateTime<Utc>,
@@ -1,10 +1,19 @@
 //! Module for operator stats.
 
+mod ctu;
+mod operator;
+mod role;
+
+pub use self::ctu::Ctu;
+pub use self::operator::{Metadata, Operator};
+pub use self::role::Role;
+
+use crate::internals::impl_bitwise_eq;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Deserialized operator stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct OperatorStats {
     pub username: String,
@@ -18,7 +27,7 @@ pub struct OperatorStats {
 }
 
 /// Deserialized operator info.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct OperatorInfo {
     pub name: String,
@@ -27,22 +36,85 @@ pub struct OperatorInfo {
     pub kills: u32,
     pub deaths: u32,
     pub kd: f32,
-    pub wins: u16,
-    pub losses: u16,
+    pub wins: u32,
+    pub losses: u32,
     pub wl: f32,
     pub headshots: u32,
     pub dbnos: u32,
-    pub melee_kills: u16,
+    pub melee_kills: u32,
     pub experience: u64,
     pub playtime: u64,
     pub abilities: Option<Vec<AbilityInfo>>,
     pub badge_image: Option<String>,
 }
 
+impl_bitwise_eq!(OperatorInfo {
+    name,
+    ctu,
+    role,
+    kills,
+    deaths,
+    wins,
+    losses,
+    headshots,
+    dbnos,
+    melee_kills,
+    experience,
+    playtime,
+    abilities,
+    badge_image,
+} floats {
+    kd,
+    wl,
+});
+
+impl OperatorInfo {
+    /// Returns the [`Operator`] of the [`name`].
+    ///
+    /// [`Operator`]: enum.Operator.html
+    /// [`name`]: #structfield.name
+    pub fn operator(&self) -> Operator {
+        Operator::from_name(&self.name)
+    }
+
+    /// Returns the [`Role`] of the [`role`], or the one of the operator's [`Metadata`] if the
+    /// api sent an unknown role.
+    ///
+    /// [`Role`]: enum.Role.html
+    /// [`role`]: #structfield.role
+    /// [`Metadata`]: struct.Metadata.html
+    pub fn role(&self) -> Option<Role> {
+        Role::from_name(&self.role).or_else(|| self.operator().metadata().map(|m| m.role))
+    }
+
+    /// Returns the [`Ctu`] of the [`ctu`], or the one of the operator's [`Metadata`] if the
+    /// api sent an unknown CTU.
+    ///
+    /// [`Ctu`]: enum.Ctu.html
+    /// [`ctu`]: #structfield.ctu
+    /// [`Metadata`]: struct.Metadata.html
+    pub fn ctu(&self) -> Option<Ctu> {
+        Ctu::from_name(&self.ctu).or_else(|| self.operator().metadata().map(|m| m.ctu))
+    }
+}
+
 /// Deserialized ability info.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct AbilityInfo {
     pub ability: String,
-    pub value: Option<u16>,
+    pub value: Option<u32>,
+}
+
+/// Lowercases the name and strips it of everything but letters and digits, and of accents.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter_map(|c| match c {
+            'ä' | 'Ä' | 'ã' | 'Ã' | 'á' | 'Á' => Some('a'),
+            'é' | 'É' => Some('e'),
+            'ø' | 'Ø' | 'ö' | 'Ö' | 'ó' | 'Ó' => Some('o'),
+            c if c.is_ascii_alphanumeric() => Some(c.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
 }
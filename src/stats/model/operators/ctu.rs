@@ -0,0 +1,69 @@
+use super::normalize;
+use std::fmt::{self, Display, Formatter};
+
+macro_rules! ctus {
+    ($($variant:ident => $name:expr,)*) => {
+        /// The counter-terrorism unit an operator belongs to.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum Ctu {
+            $($variant,)*
+        }
+
+        impl Ctu {
+            /// Returns the CTU of the name sent by the api, ignoring case, spaces and accents.
+            pub fn from_name(name: &str) -> Option<Self> {
+                let name = normalize(name);
+                $(
+                    if name == normalize($name) {
+                        return Some(Self::$variant);
+                    }
+                )*
+                None
+            }
+
+            /// Returns the string representation for the api.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+ctus! {
+    Sas => "SAS",
+    FbiSwat => "FBI SWAT",
+    Gign => "GIGN",
+    Spetsnaz => "Spetsnaz",
+    Gsg9 => "GSG 9",
+    Jtf2 => "JTF2",
+    NavySeal => "Navy SEAL",
+    Bope => "BOPE",
+    Sat => "SAT",
+    Geo => "GEO",
+    Sdu => "SDU",
+    Grom => "GROM",
+    Smb707 => "707th SMB",
+    Cbrn => "CBRN",
+    Gis => "GIS",
+    Gsutr => "GSUTR",
+    Gigr => "GIGR",
+    Sasr => "SASR",
+    JaegerCorps => "Jaeger Corps",
+    SecretService => "Secret Service",
+    Apca => "APCA",
+    Fes => "FES",
+    Nighthaven => "NIGHTHAVEN",
+    Reu => "REU",
+    Unaffiliated => "Unaffiliated",
+    InkabaTaskForce => "Inkaba Task Force",
+    Ros => "ROS",
+}
+
+impl Display for Ctu {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,164 @@
+use super::{normalize, Ctu, Role};
+use crate::stats::model::seasonal::Season;
+use std::fmt::{self, Display, Formatter};
+
+/// Static info about an [`Operator`].
+///
+/// [`Operator`]: enum.Operator.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Metadata {
+    pub role: Role,
+    pub ctu: Ctu,
+    /// The name of the unique ability.
+    pub ability: &'static str,
+    /// The season the operator was released in.
+    ///
+    /// `None` for operators released before [`Season::Health`], the first season of the api.
+    ///
+    /// [`Season::Health`]: ../seasonal/enum.Season.html#variant.Health
+    pub release_season: Option<Season>,
+}
+
+macro_rules! operators {
+    ($($variant:ident => $name:expr, $role:ident, $ctu:ident, $ability:expr, $season:expr;)*) => {
+        /// The operators of the game.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum Operator {
+            $($variant,)*
+            /// The recruit of any CTU.
+            Recruit,
+            /// For new operators not yet implemented in this client.
+            Unknown(String),
+        }
+
+        impl Operator {
+            /// Returns the operator of the name sent by the api, ignoring case, spaces and
+            /// accents.
+            pub fn from_name(name: &str) -> Self {
+                let normalized = normalize(name);
+                $(
+                    if normalized == normalize($name) {
+                        return Self::$variant;
+                    }
+                )*
+                if normalized == "recruit" {
+                    return Self::Recruit;
+                }
+                Self::Unknown(name.to_string())
+            }
+
+            /// Returns the name of the operator.
+            pub fn name(&self) -> &str {
+                match self {
+                    $(Self::$variant => $name,)*
+                    Self::Recruit => "Recruit",
+                    Self::Unknown(name) => name,
+                }
+            }
+
+            /// Returns the [`Metadata`] of the operator.
+            ///
+            /// `None` for the recruits, which differ by CTU, and unknown operators.
+            ///
+            /// [`Metadata`]: struct.Metadata.html
+            pub fn metadata(&self) -> Option<Metadata> {
+                match self {
+                    $(
+                        Self::$variant => Some(Metadata {
+                            role: Role::$role,
+                            ctu: Ctu::$ctu,
+                            ability: $ability,
+                            release_season: $season,
+                        }),
+                    )*
+                    Self::Recruit | Self::Unknown(_) => None,
+                }
+            }
+        }
+    };
+}
+
+operators! {
+    // Launch
+    Sledge => "Sledge", Attacker, Sas, "Tactical Breaching Hammer", None;
+    Thatcher => "Thatcher", Attacker, Sas, "EMP Grenade", None;
+    Smoke => "Smoke", Defender, Sas, "Remote Gas Grenade", None;
+    Mute => "Mute", Defender, Sas, "Signal Disruptor", None;
+    Ash => "Ash", Attacker, FbiSwat, "Breaching Rounds", None;
+    Thermite => "Thermite", Attacker, FbiSwat, "Exothermic Charge", None;
+    Castle => "Castle", Defender, FbiSwat, "Armor Panel", None;
+    Pulse => "Pulse", Defender, FbiSwat, "Cardiac Sensor", None;
+    Twitch => "Twitch", Attacker, Gign, "Shock Drone", None;
+    Montagne => "Montagne", Attacker, Gign, "Extendable Shield", None;
+    Doc => "Doc", Defender, Gign, "Stim Pistol", None;
+    Rook => "Rook", Defender, Gign, "Armor Pack", None;
+    Glaz => "Glaz", Attacker, Spetsnaz, "Flip Sight", None;
+    Fuze => "Fuze", Attacker, Spetsnaz, "Cluster Charge", None;
+    Kapkan => "Kapkan", Defender, Spetsnaz, "Entry Denial Device", None;
+    Tachanka => "Tachanka", Defender, Spetsnaz, "Mounted LMG", None;
+    Blitz => "Blitz", Attacker, Gsg9, "Flash Shield", None;
+    Iq => "IQ", Attacker, Gsg9, "Electronics Detector", None;
+    Jager => "Jäger", Defender, Gsg9, "Active Defense System", None;
+    Bandit => "Bandit", Defender, Gsg9, "Shock Wire", None;
+
+    // Year 1
+    Buck => "Buck", Attacker, Jtf2, "Skeleton Key", None;
+    Frost => "Frost", Defender, Jtf2, "Welcome Mat", None;
+    Blackbeard => "Blackbeard", Attacker, NavySeal, "Rifle Shield", None;
+    Valkyrie => "Valkyrie", Defender, NavySeal, "Black Eye", None;
+    Capitao => "Capitão", Attacker, Bope, "Tactical Crossbow", None;
+    Caveira => "Caveira", Defender, Bope, "Silent Step", None;
+    Hibana => "Hibana", Attacker, Sat, "X-KAIROS", None;
+    Echo => "Echo", Defender, Sat, "Yokai", None;
+
+    // Year 2
+    Jackal => "Jackal", Attacker, Geo, "Eyenox Model III", None;
+    Mira => "Mira", Defender, Geo, "Black Mirror", None;
+    Ying => "Ying", Attacker, Sdu, "Candela", Some(Season::BloodOrchid);
+    Lesion => "Lesion", Defender, Sdu, "Gu Mines", Some(Season::BloodOrchid);
+    Ela => "Ela", Defender, Grom, "Grzmot Mine", Some(Season::BloodOrchid);
+    Zofia => "Zofia", Attacker, Grom, "KS79 Lifeline", Some(Season::WhiteNoise);
+    Dokkaebi => "Dokkaebi", Attacker, Smb707, "Logic Bomb", Some(Season::WhiteNoise);
+    Vigil => "Vigil", Defender, Smb707, "ERC-7", Some(Season::WhiteNoise);
+
+    // Year 3
+    Lion => "Lion", Attacker, Cbrn, "EE-ONE-D", Some(Season::Chimera);
+    Finka => "Finka", Attacker, Cbrn, "Adrenal Surge", Some(Season::Chimera);
+    Maestro => "Maestro", Defender, Gis, "Evil Eye", Some(Season::ParaBellum);
+    Alibi => "Alibi", Defender, Gis, "Prisma", Some(Season::ParaBellum);
+    Maverick => "Maverick", Attacker, Gsutr, "Breaching Torch", Some(Season::GrimSky);
+    Clash => "Clash", Defender, Gsutr, "CCE Shield", Some(Season::GrimSky);
+    Nomad => "Nomad", Attacker, Gigr, "Airjab Launcher", Some(Season::WindBastion);
+    Kaid => "Kaid", Defender, Gigr, "Rtila Electroclaw", Some(Season::WindBastion);
+
+    // Year 4
+    Gridlock => "Gridlock", Attacker, Sasr, "Trax Stingers", Some(Season::BurntHorizon);
+    Mozzie => "Mozzie", Defender, Sasr, "Pest Launcher", Some(Season::BurntHorizon);
+    Nokk => "Nøkk", Attacker, JaegerCorps, "HEL Presence Reduction", Some(Season::PhantomSight);
+    Warden => "Warden", Defender, SecretService, "Glance Smart Glasses", Some(Season::PhantomSight);
+    Amaru => "Amaru", Attacker, Apca, "Garra Hook", Some(Season::EmberRise);
+    Goyo => "Goyo", Defender, Fes, "Volcán Shield", Some(Season::EmberRise);
+    Kali => "Kali", Attacker, Nighthaven, "LV Explosive Lance", Some(Season::ShiftingTides);
+    Wamai => "Wamai", Defender, Nighthaven, "Mag-NET System", Some(Season::ShiftingTides);
+
+    // Year 5
+    Iana => "Iana", Attacker, Reu, "Gemini Replicator", Some(Season::VoidEdge);
+    Oryx => "Oryx", Defender, Unaffiliated, "Remah Dash", Some(Season::VoidEdge);
+    Ace => "Ace", Attacker, Nighthaven, "S.E.L.M.A. Aqua Breacher", Some(Season::SteelWave);
+    Melusi => "Melusi", Defender, InkabaTaskForce, "Banshee Sonic Defense", Some(Season::SteelWave);
+    Zero => "Zero", Attacker, Ros, "Argus Launcher", Some(Season::ShadowLegacy);
+}
+
+impl From<&str> for Operator {
+    fn from(name: &str) -> Self {
+        Self::from_name(name)
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
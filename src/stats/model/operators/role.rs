@@ -0,0 +1,36 @@
+use std::fmt::{self, Display, Formatter};
+
+/// The side an operator plays on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    Attacker,
+    Defender,
+}
+
+impl Role {
+    /// Returns the role of the name sent by the api, ignoring the case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "attacker" => Some(Self::Attacker),
+            "defender" => Some(Self::Defender),
+            _ => None,
+        }
+    }
+
+    /// Returns the string representation for the api.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Attacker => "attacker",
+            Self::Defender => "defender",
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Attacker => write!(f, "Attacker"),
+            Self::Defender => write!(f, "Defender"),
+        }
+    }
+}
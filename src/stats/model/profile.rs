@@ -0,0 +1,190 @@
+//! Module for the combined stats of a player.
+
+use super::generic::{Alias, Progression, StatsInfo};
+use super::operators::OperatorInfo;
+use super::seasonal::{serialize_seasons_option, Season, SeasonInfo};
+use super::weapon_categories::CategoriesInfo;
+use super::weapons::WeaponsInfo;
+use super::{GenericStats, OperatorStats, SeasonalStats, WeaponCategoryStats, WeaponStats};
+use crate::stats::Kind;
+use crate::Error;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The stats of all [`Kind`]s of a player.
+///
+/// The player info repeated in every response is merged. The stats of a [`Kind`] whose request
+/// failed are `None`, and the error is kept in [`errors`], which isn't serialized.
+///
+/// [`Kind`]: ../enum.Kind.html
+/// [`errors`]: #structfield.errors
+#[derive(Serialize, Clone, Debug)]
+#[non_exhaustive]
+pub struct PlayerProfile {
+    pub username: String,
+    pub platform: String,
+    pub ubisoft_id: String,
+    pub uplay_id: Option<String>,
+    pub avatar_url_146: Option<String>,
+    pub avatar_url_256: Option<String>,
+    /// The most recent update of all received stats.
+    pub last_updated: DateTime<Utc>,
+    pub aliases: Option<Vec<Alias>>,
+    pub progression: Option<Progression>,
+    pub stats: Option<StatsInfo>,
+    #[serde(serialize_with = "serialize_seasons_option")]
+    pub seasons: Option<HashMap<Season, SeasonInfo>>,
+    pub operators: Option<Vec<OperatorInfo>>,
+    pub weapon_categories: Option<Vec<CategoriesInfo>>,
+    pub weapons: Option<Vec<WeaponsInfo>>,
+    /// The errors of the [`Kind`]s whose request failed.
+    ///
+    /// The errors are shared between clones of the profile.
+    ///
+    /// [`Kind`]: ../enum.Kind.html
+    #[serde(skip)]
+    pub errors: HashMap<Kind, Arc<Error>>,
+}
+
+impl PartialEq for PlayerProfile {
+    /// Compares the stats, and the [`Kind`]s whose request failed. The errors themselves can't
+    /// be compared.
+    ///
+    /// [`Kind`]: ../enum.Kind.html
+    fn eq(&self, other: &Self) -> bool {
+        self.username == other.username
+            && self.platform == other.platform
+            && self.ubisoft_id == other.ubisoft_id
+            && self.uplay_id == other.uplay_id
+            && self.avatar_url_146 == other.avatar_url_146
+            && self.avatar_url_256 == other.avatar_url_256
+            && self.last_updated == other.last_updated
+            && self.aliases == other.aliases
+            && self.progression == other.progression
+            && self.stats == other.stats
+            && self.seasons == other.seasons
+            && self.operators == other.operators
+            && self.weapon_categories == other.weapon_categories
+            && self.weapons == other.weapons
+            && self.errors.len() == other.errors.len()
+            && self
+                .errors
+                .keys()
+                .all(|kind| other.errors.contains_key(kind))
+    }
+}
+
+impl PlayerProfile {
+    /// Returns true if the stats of all [`Kind`]s were received.
+    ///
+    /// [`Kind`]: ../enum.Kind.html
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Merges the responses of all kinds.
+    ///
+    /// Fails with the first error if all requests failed.
+    pub(crate) fn merge(
+        generic: Result<GenericStats, Error>,
+        seasonal: Result<SeasonalStats, Error>,
+        operators: Result<OperatorStats, Error>,
+        weapon_categories: Result<WeaponCategoryStats, Error>,
+        weapons: Result<WeaponStats, Error>,
+    ) -> Result<Self, Error> {
+        let mut player: Option<Player> = None;
+        let mut errors = HashMap::new();
+
+        // Takes the player info out of a response, leaving the stats behind
+        macro_rules! take {
+            ($result:expr, $kind:expr) => {
+                match $result {
+                    Ok(mut stats) => {
+                        let next = Player {
+                            username: std::mem::take(&mut stats.username),
+                            platform: std::mem::take(&mut stats.platform),
+                            ubisoft_id: std::mem::take(&mut stats.ubisoft_id),
+                            uplay_id: stats.uplay_id.take(),
+                            avatar_url_146: stats.avatar_url_146.take(),
+                            avatar_url_256: stats.avatar_url_256.take(),
+                            last_updated: stats.last_updated,
+                        };
+                        player = Some(match player.take() {
+                            Some(player) => player.merge(next),
+                            None => next,
+                        });
+                        Some(stats)
+                    }
+                    Err(e) => {
+                        errors.insert($kind, e);
+                        None
+                    }
+                }
+            };
+        }
+
+        let generic = take!(generic, Kind::Generic);
+        let seasonal = take!(seasonal, Kind::Seasonal);
+        let operators = take!(operators, Kind::Operators);
+        let weapon_categories = take!(weapon_categories, Kind::WeaponCategories);
+        let weapons = take!(weapons, Kind::Weapons);
+
+        let player = match player {
+            Some(player) => player,
+            None => return Err(errors.remove(&Kind::Generic).unwrap()),
+        };
+        let errors = errors
+            .into_iter()
+            .map(|(kind, err)| (kind, Arc::new(err)))
+            .collect();
+
+        let (aliases, progression, stats) = match generic {
+            Some(g) => (Some(g.aliases), Some(g.progression), Some(g.stats)),
+            None => (None, None, None),
+        };
+
+        Ok(Self {
+            username: player.username,
+            platform: player.platform,
+            ubisoft_id: player.ubisoft_id,
+            uplay_id: player.uplay_id,
+            avatar_url_146: player.avatar_url_146,
+            avatar_url_256: player.avatar_url_256,
+            last_updated: player.last_updated,
+            aliases,
+            progression,
+            stats,
+            seasons: seasonal.map(|s| s.seasons),
+            operators: operators.map(|o| o.operators),
+            weapon_categories: weapon_categories.map(|w| w.categories),
+            weapons: weapons.map(|w| w.weapons),
+            errors,
+        })
+    }
+}
+
+/// The player info repeated in every response.
+struct Player {
+    username: String,
+    platform: String,
+    ubisoft_id: String,
+    uplay_id: Option<String>,
+    avatar_url_146: Option<String>,
+    avatar_url_256: Option<String>,
+    last_updated: DateTime<Utc>,
+}
+
+impl Player {
+    /// Fills in the info missing from `self`.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            uplay_id: self.uplay_id.or(other.uplay_id),
+            avatar_url_146: self.avatar_url_146.or(other.avatar_url_146),
+            avatar_url_256: self.avatar_url_256.or(other.avatar_url_256),
+            last_updated: self.last_updated.max(other.last_updated),
+            ..self
+        }
+    }
+}
@@ -8,15 +8,15 @@ pub use self::match_result::MatchResult;
 pub use self::rank::Rank;
 pub use self::season::Season;
 
-use crate::internals::utils::serde_parse_f64_option;
+use crate::internals::impl_bitwise_eq;
+use crate::internals::utils::{serde_display_f64_option, serde_parse_f64_option};
 use crate::region::Region;
 use chrono::{DateTime, NaiveDate, Utc};
-use int_enum::IntEnum;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 
 /// Deserialized seasonal stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct SeasonalStats {
     pub username: String,
@@ -26,11 +26,13 @@ pub struct SeasonalStats {
     pub avatar_url_146: Option<String>,
     pub avatar_url_256: Option<String>,
     pub last_updated: DateTime<Utc>,
+    #[serde(deserialize_with = "deserialize_seasons")]
+    #[serde(serialize_with = "serialize_seasons")]
     pub seasons: HashMap<Season, SeasonInfo>,
 }
 
 /// Deserialized season info.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct SeasonInfo {
     pub name: String,
@@ -40,15 +42,16 @@ pub struct SeasonInfo {
 }
 
 /// Deserialized region info.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct RegionInfo {
     #[serde(rename = "season_id")]
     #[serde(deserialize_with = "deserialize_season")]
+    #[serde(serialize_with = "serialize_season")]
     pub season: Season,
     pub region: String,
-    pub abandons: u16,
-    pub losses: u16,
+    pub abandons: u32,
+    pub losses: u32,
     pub max_mmr: f32,
     pub max_rank: Rank,
     pub mmr: f32,
@@ -58,15 +61,17 @@ pub struct RegionInfo {
     pub skill_mean: f32,
     pub skill_standard_deviation: f32,
     pub created_for_date: DateTime<Utc>,
-    pub wins: u16,
-    pub kills: Option<u16>,
-    pub deaths: Option<u16>,
+    pub wins: u32,
+    pub kills: Option<u32>,
+    pub deaths: Option<u32>,
     pub last_match_mmr_change: Option<i16>,
     // The endpoint returns a string for this field
     #[serde(deserialize_with = "serde_parse_f64_option")]
+    #[serde(serialize_with = "serde_display_f64_option")]
     pub last_match_skill_mean_change: Option<f64>,
     // The endpoint returns a string for this field
     #[serde(deserialize_with = "serde_parse_f64_option")]
+    #[serde(serialize_with = "serde_display_f64_option")]
     pub last_match_skill_standard_deviation_change: Option<f64>,
     pub last_match_result: Option<MatchResult>,
     pub champions_rank_position: Option<u16>,
@@ -76,7 +81,100 @@ pub struct RegionInfo {
     pub max_rank_image: String,
 }
 
+impl_bitwise_eq!(RegionInfo {
+    season,
+    region,
+    abandons,
+    losses,
+    max_rank,
+    rank,
+    created_for_date,
+    wins,
+    kills,
+    deaths,
+    last_match_mmr_change,
+    last_match_result,
+    champions_rank_position,
+    rank_text,
+    rank_image,
+    max_rank_text,
+    max_rank_image,
+} floats {
+    max_mmr,
+    mmr,
+    next_rank_mmr,
+    prev_rank_mmr,
+    skill_mean,
+    skill_standard_deviation,
+    last_match_skill_mean_change,
+    last_match_skill_standard_deviation_change,
+});
+
 fn deserialize_season<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Season, D::Error> {
-    let id = u8::deserialize(deserializer)?;
-    Season::from_int(id).map_err(serde::de::Error::custom)
+    // Seasons released after this client are mapped to `Season::Unknown`, keeping their id
+    u8::deserialize(deserializer).map(Season::from_id)
+}
+
+fn serialize_season<S: Serializer>(season: &Season, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u8(season.id())
+}
+
+/// Deserializes the seasons keyed by their name.
+///
+/// The api keys seasons by name only, so unknown seasons take the id of their regions, or `0`
+/// if they have none.
+fn deserialize_seasons<'de, D>(deserializer: D) -> Result<HashMap<Season, SeasonInfo>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let seasons = HashMap::<String, SeasonInfo>::deserialize(deserializer)?;
+
+    Ok(seasons
+        .into_iter()
+        .map(|(key, info)| {
+            let season = Season::parse(&key)
+                .or_else(|| info.regions.values().flatten().next().map(|r| r.season))
+                .unwrap_or(Season::Unknown(0));
+            (season, info)
+        })
+        .collect())
+}
+
+fn serialize_seasons<S>(
+    seasons: &HashMap<Season, SeasonInfo>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    SerializeSeasons(seasons).serialize(serializer)
+}
+
+pub(crate) fn serialize_seasons_option<S>(
+    seasons: &Option<HashMap<Season, SeasonInfo>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    seasons.as_ref().map(SerializeSeasons).serialize(serializer)
+}
+
+/// Serializes the seasons keyed by their name, as sent by the api.
+struct SerializeSeasons<'a>(&'a HashMap<Season, SeasonInfo>);
+
+impl Serialize for SerializeSeasons<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(season, info)| {
+            let key = match season.key() {
+                Some(key) => key.to_string(),
+                // Read back through the id of the regions, like the name sent by the api
+                None if info.regions.values().any(|r| !r.is_empty()) => {
+                    info.name.to_lowercase().replace(' ', "_")
+                }
+                None => format!("unknown_{}", season.id()),
+            };
+            (key, info)
+        }))
+    }
 }
@@ -1,68 +1,115 @@
-use int_enum::IntEnum;
-use serde::Deserialize;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
 use std::fmt::{self, Display, Formatter};
 
-/// All seasons available in the api.
-#[derive(IntEnum, Deserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[serde(rename_all = "snake_case")]
-#[non_exhaustive]
-#[repr(u8)]
-pub enum Season {
+macro_rules! seasons {
+    ($($variant:ident => $id:expr, $key:expr, $name:expr;)*) => {
+        /// All seasons available in the api.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[non_exhaustive]
+        pub enum Season {
+            $($variant,)*
+            /// For new seasons not yet implemented in this client, with the id sent by the api.
+            Unknown(u8),
+        }
+
+        impl Season {
+            /// Returns the season of the id sent by the api.
+            pub fn from_id(id: u8) -> Self {
+                match id {
+                    $($id => Self::$variant,)*
+                    id => Self::Unknown(id),
+                }
+            }
+
+            /// Returns the id of the season in the api.
+            pub fn id(self) -> u8 {
+                match self {
+                    $(Self::$variant => $id,)*
+                    Self::Unknown(id) => id,
+                }
+            }
+
+            /// Returns the season of the key of the `seasons` map sent by the api.
+            fn from_key(key: &str) -> Option<Self> {
+                match key {
+                    $($key => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the key of the season in the `seasons` map of the api, `None` if unknown.
+            pub(crate) fn key(self) -> Option<&'static str> {
+                match self {
+                    $(Self::$variant => Some($key),)*
+                    Self::Unknown(_) => None,
+                }
+            }
+        }
+
+        impl Display for Season {
+            fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                match self {
+                    $(Self::$variant => write!(f, $name),)*
+                    Self::Unknown(id) => write!(f, "Unknown ({})", id),
+                }
+            }
+        }
+    };
+}
+
+seasons! {
     // Year 2
-    Health = 6,
-    BloodOrchid = 7,
-    WhiteNoise = 8,
+    Health => 6, "health", "Health";
+    BloodOrchid => 7, "blood_orchid", "Blood Orchid";
+    WhiteNoise => 8, "white_noise", "White Noise";
 
     // Year 3
-    Chimera = 9,
-    ParaBellum = 10,
-    GrimSky = 11,
-    WindBastion = 12,
+    Chimera => 9, "chimera", "Chimera";
+    ParaBellum => 10, "para_bellum", "Para Bellum";
+    GrimSky => 11, "grim_sky", "Grim Sky";
+    WindBastion => 12, "wind_bastion", "Wind Bastion";
 
     // Year 4
-    BurntHorizon = 13,
-    PhantomSight = 14,
-    EmberRise = 15,
-    ShiftingTides = 16,
+    BurntHorizon => 13, "burnt_horizon", "Burnt Horizon";
+    PhantomSight => 14, "phantom_sight", "Phantom Sight";
+    EmberRise => 15, "ember_rise", "Ember Rise";
+    ShiftingTides => 16, "shifting_tides", "Shifting Tides";
 
     // Year 5
-    VoidEdge = 17,
-    SteelWave = 18,
-    ShadowLegacy = 19,
-
-    /// For new seasons not yet implemented in this client.
-    #[serde(other)]
-    Unknown = 20,
+    VoidEdge => 17, "void_edge", "Void Edge";
+    SteelWave => 18, "steel_wave", "Steel Wave";
+    ShadowLegacy => 19, "shadow_legacy", "Shadow Legacy";
 }
 
 impl Season {
     pub const fn current_season() -> Self {
         Self::ShadowLegacy
     }
-}
-
-impl Display for Season {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self {
-            Self::Health => write!(f, "Health"),
-            Self::BloodOrchid => write!(f, "Blood Orchid"),
-            Self::WhiteNoise => write!(f, "White Noise"),
-
-            Self::Chimera => write!(f, "Chimera"),
-            Self::ParaBellum => write!(f, "Para Bellum"),
-            Self::GrimSky => write!(f, "Grim Sky"),
-            Self::WindBastion => write!(f, "Wind Bastion"),
-
-            Self::BurntHorizon => write!(f, "Burnt Horizon"),
-            Self::PhantomSight => write!(f, "Phantom Sight"),
-            Self::EmberRise => write!(f, "Ember Rise"),
-            Self::ShiftingTides => write!(f, "Shifting Tides"),
 
-            Self::VoidEdge => write!(f, "Void Edge"),
-            Self::SteelWave => write!(f, "Steel Wave"),
-            Self::ShadowLegacy => write!(f, "Shadow Legacy"),
+    /// Parses a key of the api or `unknown_<id>`, as the season is serialized.
+    pub(crate) fn parse(key: &str) -> Option<Self> {
+        Self::from_key(key).or_else(|| {
+            key.strip_prefix("unknown_")
+                .and_then(|id| id.parse().ok())
+                .map(Self::Unknown)
+        })
+    }
+}
 
-            Self::Unknown => write!(f, "Unknown"),
+/// Serialized as the key of the season in the api, or `unknown_<id>` for unknown seasons.
+impl Serialize for Season {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.key() {
+            Some(key) => serializer.serialize_str(key),
+            None => serializer.collect_str(&format_args!("unknown_{}", self.id())),
         }
     }
 }
+
+impl<'de> Deserialize<'de> for Season {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        Self::parse(&key).ok_or_else(|| de::Error::custom(format_args!("unknown season `{}`", key)))
+    }
+}
@@ -1,10 +1,11 @@
 //! Module for weapon category stats.
 
+use crate::internals::impl_bitwise_eq;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Deserialized weapon category stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct WeaponCategoryStats {
     pub username: String,
@@ -18,7 +19,7 @@ pub struct WeaponCategoryStats {
 }
 
 /// Deserialized category info.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct CategoriesInfo {
     pub category: String,
@@ -33,3 +34,18 @@ pub struct CategoriesInfo {
     pub created: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
 }
+
+impl_bitwise_eq!(CategoriesInfo {
+    category,
+    kills,
+    deaths,
+    headshots,
+    times_chosen,
+    bullets_fired,
+    bullets_hit,
+    created,
+    last_updated,
+} floats {
+    kd,
+    headshot_percentage,
+});
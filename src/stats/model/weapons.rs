@@ -1,10 +1,11 @@
 //! Module for weapon stats.
 
+use crate::internals::impl_bitwise_eq;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Deserialized weapon stats.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct WeaponStats {
     pub username: String,
@@ -18,7 +19,7 @@ pub struct WeaponStats {
 }
 
 /// Deserialized weapon info.
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[non_exhaustive]
 pub struct WeaponsInfo {
     pub weapon: String,
@@ -28,9 +29,25 @@ pub struct WeaponsInfo {
     pub kd: f32,
     pub headshots: u32,
     pub headshot_percentage: f32,
-    pub times_chosen: u16,
+    pub times_chosen: u32,
     pub bullets_fired: u64,
     pub bullets_hit: u64,
     pub created: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
 }
+
+impl_bitwise_eq!(WeaponsInfo {
+    weapon,
+    category,
+    kills,
+    deaths,
+    headshots,
+    times_chosen,
+    bullets_fired,
+    bullets_hit,
+    created,
+    last_updated,
+} floats {
+    kd,
+    headshot_percentage,
+});
@@ -0,0 +1,163 @@
+//! Module for identifying players.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Identifies a player.
+///
+/// The API only looks up players by username. Players given by their Ubisoft ID are looked up
+/// by the username the [`Resolver`] knows for the ID.
+///
+/// `&str` and `String` convert into a [`PlayerId::Username`].
+///
+/// [`Resolver`]: struct.Resolver.html
+/// [`PlayerId::Username`]: #variant.Username
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PlayerId {
+    /// The current username of the player.
+    Username(String),
+    /// The Ubisoft ID of the player, which stays the same when the username changes.
+    UbisoftId(String),
+}
+
+impl From<&str> for PlayerId {
+    fn from(username: &str) -> Self {
+        Self::Username(username.to_string())
+    }
+}
+
+impl From<&String> for PlayerId {
+    fn from(username: &String) -> Self {
+        Self::Username(username.clone())
+    }
+}
+
+impl From<String> for PlayerId {
+    fn from(username: String) -> Self {
+        Self::Username(username)
+    }
+}
+
+impl Display for PlayerId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Username(username) => Display::fmt(username, f),
+            Self::UbisoftId(id) => write!(f, "Ubisoft ID {}", id),
+        }
+    }
+}
+
+const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Maps Ubisoft IDs to the current username of the player.
+///
+/// The mapping is learned from the players in the responses of the stats, leaderboard and search
+/// endpoints, and can be seeded with [`insert`], e.g. from a database of known players. A
+/// username which changed since it was learned is only corrected by the next response listing
+/// the player.
+///
+/// Up to [`capacity`] IDs are known, 10 000 by default. The least recently used ones are
+/// forgotten first.
+///
+/// [`insert`]: #method.insert
+/// [`capacity`]: #method.capacity
+#[derive(Debug)]
+pub struct Resolver {
+    inner: Mutex<Lru>,
+}
+
+impl Resolver {
+    /// Returns a resolver knowing up to `capacity` Ubisoft IDs.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Lru {
+                capacity,
+                usernames: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Returns the username known for the Ubisoft ID.
+    pub fn username(&self, ubisoft_id: &str) -> Option<String> {
+        self.lock().get(ubisoft_id)
+    }
+
+    /// Sets the username of the Ubisoft ID.
+    pub fn insert(&self, ubisoft_id: impl Into<String>, username: impl Into<String>) {
+        self.lock().insert(ubisoft_id.into(), username.into());
+    }
+
+    /// The maximum number of known Ubisoft IDs.
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// The number of known Ubisoft IDs.
+    pub fn len(&self) -> usize {
+        self.lock().usernames.len()
+    }
+
+    /// Returns true if no Ubisoft IDs are known.
+    pub fn is_empty(&self) -> bool {
+        self.lock().usernames.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    /// The usernames by Ubisoft ID, along with the tick they were last used at.
+    usernames: HashMap<String, (String, u64)>,
+    /// The Ubisoft IDs by the tick they were last used at, least recently used first.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, ubisoft_id: &str) -> Option<String> {
+        let tick = self.next_tick();
+        let (username, used_at) = self.usernames.get_mut(ubisoft_id)?;
+
+        let ubisoft_id = self.recency.remove(used_at).unwrap();
+        self.recency.insert(tick, ubisoft_id);
+        *used_at = tick;
+
+        Some(username.clone())
+    }
+
+    fn insert(&mut self, ubisoft_id: String, username: String) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let tick = self.next_tick();
+        if let Some((_, used_at)) = self.usernames.insert(ubisoft_id.clone(), (username, tick)) {
+            self.recency.remove(&used_at);
+        }
+        self.recency.insert(tick, ubisoft_id);
+
+        while self.usernames.len() > self.capacity {
+            let (_, oldest) = self.recency.pop_first().unwrap();
+            self.usernames.remove(&oldest);
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
@@ -0,0 +1,367 @@
+//! Offline test harness.
+//!
+//! [`MockServer`] is a local HTTP server standing in for the r6stats API. It serves recorded
+//! JSON fixtures, records every request it receives and can check the bearer token, so tests
+//! run without an API key or network access.
+//!
+//! ## Example
+//! ```rust
+//! use r6stats_client::stats::Kind;
+//! use r6stats_client::test_util::MockServer;
+//! use r6stats_client::Platform;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockServer::start().await;
+//!     server.expect_token("TOKEN");
+//!     server.mount_stats("pengu.g2", Platform::Pc, Kind::Generic);
+//!
+//!     let client = server.client_builder("TOKEN").build().unwrap();
+//!     let stats = client.stats().generic("pengu.g2", Platform::Pc).await.unwrap();
+//!
+//!     assert_eq!(stats.username, "pengu.g2");
+//!     assert_eq!(server.requests().len(), 1);
+//! }
+//! ```
+//!
+//! [`MockServer`]: struct.MockServer.html
+
+use crate::internals::utils::join_segments;
+use crate::stats::Kind;
+use crate::{ClientBuilder, Platform, Region};
+use hyper::header::{HeaderName, HeaderValue, AUTHORIZATION};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use reqwest::Url;
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tokio::time::{delay_for, Duration};
+
+/// Recorded responses of the API.
+pub mod fixtures {
+    use crate::stats::Kind;
+
+    /// Response of `/stats/{username}/{platform}/generic`.
+    pub const GENERIC: &str = include_str!("../tests/fixtures/generic.json");
+    /// Response of `/stats/{username}/{platform}/seasonal`.
+    pub const SEASONAL: &str = include_str!("../tests/fixtures/seasonal.json");
+    /// Response of `/stats/{username}/{platform}/operators`.
+    pub const OPERATORS: &str = include_str!("../tests/fixtures/operators.json");
+    /// Response of `/stats/{username}/{platform}/weapon-categories`.
+    pub const WEAPON_CATEGORIES: &str = include_str!("../tests/fixtures/weapon_categories.json");
+    /// Response of `/stats/{username}/{platform}/weapons`.
+    pub const WEAPONS: &str = include_str!("../tests/fixtures/weapons.json");
+    /// Response of `/leaderboard/{platform}/{region}`.
+    pub const LEADERBOARD: &str = include_str!("../tests/fixtures/leaderboard.json");
+    /// Response of `/search/{query}/{platform}`.
+    ///
+    /// Unlike the other fixtures, this one wasn't recorded: it's written after the player info
+    /// of the stats responses.
+    pub const SEARCH: &str = include_str!("../tests/fixtures/search.json");
+
+    /// Returns the fixture for the [`Kind`].
+    ///
+    /// [`Kind`]: ../../stats/enum.Kind.html
+    pub fn stats(kind: Kind) -> &'static str {
+        match kind {
+            Kind::Generic => GENERIC,
+            Kind::Seasonal => SEASONAL,
+            Kind::Operators => OPERATORS,
+            Kind::WeaponCategories => WEAPON_CATEGORIES,
+            Kind::Weapons => WEAPONS,
+        }
+    }
+}
+
+const NOT_FOUND_BODY: &str = r#"{"status":"error","error":"Not Found"}"#;
+const UNAUTHORIZED_BODY: &str = r#"{"status":"error","error":"Unauthorized"}"#;
+
+/// A response served by the [`MockServer`].
+///
+/// [`MockServer`]: struct.MockServer.html
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Option<Duration>,
+}
+
+impl MockResponse {
+    /// Returns an empty response with the status code.
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            delay: None,
+        }
+    }
+
+    /// Returns a `200 OK` response with the JSON body.
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::status(200)
+            .header("Content-Type", "application/json")
+            .body(body.into())
+    }
+
+    /// Adds a header to the response.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the body of the response.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Delays the response, e.g. for provoking timeouts.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    fn into_response(self) -> Response<Body> {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() =
+            StatusCode::from_u16(self.status).expect("Invalid status code in mock response.");
+
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).expect("Invalid header name.");
+            let value = HeaderValue::from_str(&value).expect("Invalid header value.");
+            headers.append(name, value);
+        }
+
+        response
+    }
+}
+
+/// A request received by the [`MockServer`].
+///
+/// [`MockServer`]: struct.MockServer.html
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RecordedRequest {
+    /// The HTTP method.
+    pub method: String,
+    /// The percent-encoded path, including the query.
+    pub path: String,
+    /// The bearer token, if the request was authenticated.
+    pub token: Option<String>,
+    /// All headers of the request.
+    pub headers: Vec<(String, String)>,
+}
+
+impl RecordedRequest {
+    /// Returns the first value of the header, if present.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    routes: HashMap<String, VecDeque<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+    token: Option<String>,
+}
+
+/// A local stand-in for the r6stats API.
+///
+/// The server listens on a random port of `127.0.0.1` and shuts down when dropped. Unknown
+/// routes are answered with `404 Not Found`.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Starts a new server on the current runtime.
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Error binding mock server.");
+        let addr = listener.local_addr().expect("Error binding mock server.");
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let response = handle(&state, request);
+                    async move {
+                        if let Some(delay) = response.delay {
+                            delay_for(delay).await;
+                        }
+                        Ok::<_, Infallible>(response.into_response())
+                    }
+                }))
+            }
+        });
+
+        let (tx, rx) = oneshot::channel();
+        let server = Server::from_tcp(listener)
+            .expect("Error starting mock server.")
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                let _ = rx.await;
+            });
+        tokio::spawn(server);
+
+        Self {
+            addr,
+            state,
+            shutdown: Some(tx),
+        }
+    }
+
+    /// The base URL of the server, to be passed to [`ClientBuilder::base_url`].
+    ///
+    /// [`ClientBuilder::base_url`]: ../struct.ClientBuilder.html#method.base_url
+    pub fn base_url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// Returns a [`ClientBuilder`] pointed at the server.
+    ///
+    /// [`ClientBuilder`]: ../struct.ClientBuilder.html
+    pub fn client_builder(&self, token: impl AsRef<str>) -> ClientBuilder {
+        ClientBuilder::new(token).base_url(self.base_url())
+    }
+
+    /// Answers every request without the bearer `token` with `401 Unauthorized`.
+    pub fn expect_token(&self, token: impl Into<String>) {
+        self.state().token = Some(token.into());
+    }
+
+    /// Serves `response` on `path`.
+    ///
+    /// The `path` is percent-encoded and starts with a `/`, e.g. `/stats/pengu.g2/pc/generic`.
+    pub fn mount(&self, path: impl Into<String>, response: MockResponse) {
+        self.mount_sequence(path, vec![response]);
+    }
+
+    /// Serves the `responses` on `path` one after another.
+    ///
+    /// The last response is repeated once the others are used up.
+    pub fn mount_sequence(&self, path: impl Into<String>, responses: Vec<MockResponse>) {
+        assert!(!responses.is_empty(), "No mock responses given.");
+        self.state()
+            .routes
+            .insert(path.into(), responses.into_iter().collect());
+    }
+
+    /// Serves the recorded fixture of the [`Kind`] for the player.
+    ///
+    /// [`Kind`]: ../stats/enum.Kind.html
+    pub fn mount_stats(&self, username: &str, platform: Platform, kind: Kind) {
+        let path = stats_path(username, platform, kind);
+        self.mount(path, MockResponse::json(fixtures::stats(kind)));
+    }
+
+    /// Serves the recorded leaderboard fixture.
+    pub fn mount_leaderboard(&self, platform: Platform, region: Option<Region>) {
+        let path = leaderboard_path(platform, region);
+        self.mount(path, MockResponse::json(fixtures::LEADERBOARD));
+    }
+
+    /// Serves the player search fixture.
+    pub fn mount_search(&self, query: &str, platform: Option<Platform>) {
+        let path = search_path(query, platform);
+        self.mount(path, MockResponse::json(fixtures::SEARCH));
+    }
+
+    /// Returns all requests received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Mock server state poisoned.")
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Returns the percent-encoded path of a stats route.
+pub fn stats_path(username: &str, platform: Platform, kind: Kind) -> String {
+    route_path(&["stats", username, platform.as_str(), kind.as_str()])
+}
+
+/// Returns the percent-encoded path of a leaderboard route.
+pub fn leaderboard_path(platform: Platform, region: Option<Region>) -> String {
+    let region = region.map(Region::as_str).unwrap_or("all");
+    route_path(&["leaderboard", platform.as_str(), region])
+}
+
+/// Returns the percent-encoded path of a player search route.
+pub fn search_path(query: &str, platform: Option<Platform>) -> String {
+    let platform = platform.map(Platform::as_str).unwrap_or("all");
+    route_path(&["search", query, platform])
+}
+
+fn route_path(segments: &[&str]) -> String {
+    let base = Url::parse("http://localhost/").expect("Invalid URL.");
+    join_segments(&base, segments).path().to_string()
+}
+
+fn handle(state: &Mutex<State>, request: Request<Body>) -> MockResponse {
+    let path = request
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "/".to_string());
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(n, v)| (n.to_string(), String::from_utf8_lossy(v.as_bytes()).into()))
+        .collect();
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string);
+
+    let mut state = state.lock().expect("Mock server state poisoned.");
+    state.requests.push(RecordedRequest {
+        method: request.method().to_string(),
+        path: path.clone(),
+        token: token.clone(),
+        headers,
+    });
+
+    if state.token.is_some() && state.token != token {
+        return MockResponse::status(401)
+            .header("Content-Type", "application/json")
+            .body(UNAUTHORIZED_BODY);
+    }
+
+    let response = match state.routes.get_mut(&path) {
+        Some(responses) if responses.len() > 1 => responses.pop_front(),
+        Some(responses) => responses.front().cloned(),
+        None => None,
+    };
+
+    response.unwrap_or_else(|| {
+        MockResponse::status(404)
+            .header("Content-Type", "application/json")
+            .body(NOT_FOUND_BODY)
+    })
+}
@@ -0,0 +1,155 @@
+use r6stats_client::http::DiskStore;
+use r6stats_client::stats::Kind;
+use r6stats_client::test_util::MockServer;
+use r6stats_client::Platform;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+const TOKEN: &str = "TOKEN";
+
+#[tokio::test]
+async fn test_cache() {
+    let server = MockServer::start().await;
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Generic);
+    server.mount_leaderboard(Platform::Pc, None);
+
+    let client = server.client_builder(TOKEN).cache(|c| c).build().unwrap();
+
+    for _ in 0..3 {
+        client
+            .stats()
+            .generic("pengu.g2", Platform::Pc)
+            .await
+            .unwrap();
+        client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    }
+
+    assert_eq!(server.requests().len(), 2);
+
+    let stats = client.cache_stats().await.unwrap();
+    assert_eq!(stats.hits(), 4);
+    assert_eq!(stats.misses(), 2);
+    assert_eq!(stats.len(), 2);
+}
+
+#[tokio::test]
+async fn test_cache_disabled_by_default() {
+    let server = MockServer::start().await;
+    server.mount_leaderboard(Platform::Pc, None);
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    for _ in 0..2 {
+        client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    }
+
+    assert_eq!(server.requests().len(), 2);
+    assert!(client.cache_stats().await.is_none());
+}
+
+#[tokio::test]
+async fn test_force_refresh() {
+    let server = MockServer::start().await;
+    server.mount_leaderboard(Platform::Pc, None);
+
+    let client = server.client_builder(TOKEN).cache(|c| c).build().unwrap();
+
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    client
+        .leaderboard()
+        .force_refresh()
+        .get(Platform::Pc, None)
+        .await
+        .unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    // The refreshed response is cached
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_stats_ttl() {
+    let server = MockServer::start().await;
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Generic);
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Seasonal);
+
+    let client = server
+        .client_builder(TOKEN)
+        .cache(|c| c.stats_ttl(Kind::Seasonal, Duration::from_secs(0)))
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        client
+            .stats()
+            .generic("pengu.g2", Platform::Pc)
+            .await
+            .unwrap();
+        client
+            .stats()
+            .seasonal("pengu.g2", Platform::Pc)
+            .await
+            .unwrap();
+    }
+
+    // Only the seasonal stats were requested twice
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_capacity() {
+    let server = MockServer::start().await;
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Generic);
+    server.mount_leaderboard(Platform::Pc, None);
+
+    let client = server
+        .client_builder(TOKEN)
+        .cache(|c| c.capacity(1))
+        .build()
+        .unwrap();
+
+    client
+        .stats()
+        .generic("pengu.g2", Platform::Pc)
+        .await
+        .unwrap();
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    client
+        .stats()
+        .generic("pengu.g2", Platform::Pc)
+        .await
+        .unwrap();
+
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(client.cache_stats().await.unwrap().evictions(), 2);
+}
+
+#[tokio::test]
+async fn test_disk_store_survives_restart() {
+    let server = MockServer::start().await;
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Generic);
+
+    let dir = std::env::temp_dir().join(format!("r6stats_client-cache-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    // Stand-ins for the process before and after a restart
+    for _ in 0..2 {
+        let client = server
+            .client_builder(TOKEN)
+            .cache(|c| c.store(DiskStore::new(&dir)))
+            .build()
+            .unwrap();
+
+        let stats = client
+            .stats()
+            .generic("pengu.g2", Platform::Pc)
+            .await
+            .unwrap();
+        assert_eq!(stats.username, "pengu.g2");
+    }
+
+    assert_eq!(server.requests().len(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
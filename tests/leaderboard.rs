@@ -1,25 +1,43 @@
-use r6stats_client::{Client, Platform, Region};
-use std::env;
+use r6stats_client::test_util::MockServer;
+use r6stats_client::{Platform, Region};
+
+const TOKEN: &str = "TOKEN";
 
 #[tokio::test]
 async fn test_leaderboard() {
-    let token = env::var("R6STATS_TOKEN").expect("API key not in env.");
-    let client = Client::new(&token).expect("Error creating client.");
+    let server = MockServer::start().await;
+    server.expect_token(TOKEN);
+
+    let routes = [
+        (Platform::Pc, None),
+        (Platform::Xbox, Some(Region::Emea)),
+        (Platform::Playstation, Some(Region::Apac)),
+        (Platform::Pc, Some(Region::Ncsa)),
+    ];
+    for &(platform, region) in routes.iter() {
+        server.mount_leaderboard(platform, region);
+    }
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    for &(platform, region) in routes.iter() {
+        let leaderboard = client.leaderboard().get(platform, region).await.unwrap();
+        assert!(!leaderboard.players.is_empty());
+    }
+
+    let paths = server
+        .requests()
+        .into_iter()
+        .map(|r| r.path)
+        .collect::<Vec<_>>();
 
-    let _ = client.leaderboard().get(Platform::Pc, None).await.unwrap();
-    let _ = client
-        .leaderboard()
-        .get(Platform::Xbox, Some(Region::Emea))
-        .await
-        .unwrap();
-    let _ = client
-        .leaderboard()
-        .get(Platform::Playstation, Some(Region::Apac))
-        .await
-        .unwrap();
-    let _ = client
-        .leaderboard()
-        .get(Platform::Pc, Some(Region::Ncsa))
-        .await
-        .unwrap();
+    assert_eq!(
+        paths,
+        [
+            "/leaderboard/pc/all",
+            "/leaderboard/xbox/emea",
+            "/leaderboard/ps4/apac",
+            "/leaderboard/pc/ncsa",
+        ]
+    );
 }
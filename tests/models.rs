@@ -0,0 +1,445 @@
+use chrono::{DateTime, Utc};
+use r6stats_client::leaderboard::model::Leaderboard;
+use r6stats_client::search::model::SearchResults;
+use r6stats_client::stats::model::generic::QueueMode;
+use r6stats_client::stats::model::operators::{Ctu, Operator, Role};
+use r6stats_client::stats::model::seasonal::{MatchResult, Rank, Season};
+use r6stats_client::stats::model::{
+    GenericStats, OperatorStats, SeasonalStats, WeaponCategoryStats, WeaponStats,
+};
+use r6stats_client::stats::Lenient;
+use r6stats_client::{Fingerprint, Region};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt::Debug;
+
+macro_rules! fixture {
+    ($name:expr) => {
+        serde_json::from_str(include_str!(concat!("fixtures/", $name, ".json")))
+            .expect(concat!("Error deserializing fixture ", $name))
+    };
+}
+
+#[test]
+fn test_generic() {
+    let stats: GenericStats = fixture!("generic");
+
+    assert_eq!(stats.username, "pengu.g2");
+    assert_eq!(stats.platform, "pc");
+    assert_eq!(
+        stats.last_updated,
+        "2020-09-12T14:03:27Z".parse::<DateTime<Utc>>().unwrap()
+    );
+    assert_eq!(stats.aliases.len(), 2);
+    assert_eq!(stats.progression.level, 312);
+    assert_eq!(stats.stats.general.kills, 29311);
+    assert_eq!(stats.stats.queue.len(), 3);
+    assert_eq!(stats.stats.queue[&QueueMode::Ranked].wins, 2003);
+    assert_eq!(stats.stats.gamemode.hostage.extractions_denied, 41);
+}
+
+#[test]
+fn test_generic_null_avatars() {
+    let stats: GenericStats = fixture!("generic_null_avatars");
+
+    assert!(stats.uplay_id.is_none());
+    assert!(stats.avatar_url_146.is_none());
+    assert!(stats.avatar_url_256.is_none());
+    assert!(stats.aliases.is_empty());
+    assert!(stats.stats.general.distance_travelled < 0);
+    assert_eq!(
+        stats.stats.general.distance_travelled_corrected(),
+        3_000_000_000
+    );
+    assert!(stats.stats.queue.contains_key(&QueueMode::Casual));
+    assert!(!stats.stats.queue.contains_key(&QueueMode::Ranked));
+}
+
+#[test]
+fn test_generic_large_counters() {
+    let mut value: Value = fixture!("generic");
+    let general = &mut value["stats"]["general"];
+    general["games_played"] = json!(70000);
+    general["revives"] = json!(65536);
+    general["distance_travelled"] = json!(112875421);
+    value["stats"]["queue"]["ranked"]["wins"] = json!(100000);
+    value["stats"]["gamemode"]["bomb"]["games_played"] = json!(80000);
+
+    let stats: GenericStats = serde_json::from_value(value).unwrap();
+
+    let general = &stats.stats.general;
+    assert_eq!(general.games_played, 70000);
+    assert_eq!(general.revives, 65536);
+    assert_eq!(general.distance_travelled_corrected(), 112875421);
+    assert_eq!(stats.stats.queue[&QueueMode::Ranked].wins, 100000);
+    assert_eq!(stats.stats.gamemode.bomb.games_played, 80000);
+}
+
+#[test]
+fn test_seasonal() {
+    let stats: SeasonalStats = fixture!("seasonal");
+
+    let current = &stats.seasons[&Season::ShadowLegacy];
+    assert_eq!(current.name, "Shadow Legacy");
+    assert!(current.end_date.is_none());
+
+    let emea = &current.regions[&Region::Emea][0];
+    assert_eq!(emea.season, Season::ShadowLegacy);
+    assert_eq!(emea.rank, Rank::Diamond);
+    assert_eq!(emea.last_match_skill_mean_change, Some(0.412));
+    assert!(matches!(emea.last_match_result, Some(MatchResult::Win)));
+
+    let unranked = &current.regions[&Region::Ncsa][0];
+    assert!(unranked.rank.is_unranked());
+    assert!(unranked.kills.is_none());
+    assert!(unranked.last_match_skill_mean_change.is_none());
+    assert!(unranked.last_match_result.is_none());
+
+    let previous = &stats.seasons[&Season::SteelWave].regions[&Region::Emea][0];
+    assert!(previous.rank.is_champion());
+    assert_eq!(previous.champions_rank_position, Some(87));
+}
+
+#[test]
+fn test_seasonal_unknown_season() {
+    let stats: SeasonalStats = fixture!("seasonal_unknown_season");
+
+    assert_eq!(stats.seasons.len(), 2);
+    assert!(stats.avatar_url_146.is_none());
+
+    let unknown = &stats.seasons[&Season::Unknown(21)];
+    assert_eq!(unknown.name, "Crimson Heist");
+
+    let region = &unknown.regions[&Region::Ncsa][0];
+    assert_eq!(region.season, Season::Unknown(21));
+    assert_eq!(region.rank, Rank::Champions);
+    assert_eq!(region.champions_rank_position, Some(3));
+
+    let known = &stats.seasons[&Season::ShadowLegacy].regions[&Region::Ncsa][0];
+    assert_eq!(known.champions_rank_position, Some(1));
+    assert!(matches!(
+        known.last_match_result,
+        Some(MatchResult::Abandoned)
+    ));
+}
+
+#[test]
+fn test_operators() {
+    let stats: OperatorStats = fixture!("operators");
+
+    assert_eq!(stats.operators.len(), 2);
+
+    let ash = &stats.operators[0];
+    assert_eq!(ash.name, "Ash");
+    assert_eq!(ash.role, "attacker");
+
+    let abilities = ash.abilities.as_ref().unwrap();
+    assert_eq!(abilities[0].ability, "Breaching Rounds");
+    assert_eq!(abilities[0].value, Some(1422));
+
+    assert_eq!(ash.operator(), Operator::Ash);
+    assert_eq!(ash.role(), Some(Role::Attacker));
+    assert_eq!(ash.ctu(), Some(Ctu::FbiSwat));
+
+    let mira = &stats.operators[1];
+    assert_eq!(mira.operator(), Operator::Mira);
+    assert_eq!(mira.role(), Some(Role::Defender));
+    assert_eq!(mira.ctu(), Some(Ctu::Geo));
+}
+
+#[test]
+fn test_operators_missing_abilities() {
+    let stats: OperatorStats = fixture!("operators_missing_abilities");
+
+    let recruit = &stats.operators[0];
+    assert!(recruit.abilities.is_none());
+    assert!(recruit.badge_image.is_none());
+
+    assert_eq!(recruit.operator(), Operator::Recruit);
+    assert_eq!(recruit.role(), Some(Role::Attacker));
+    assert_eq!(recruit.ctu(), Some(Ctu::Sas));
+
+    let zero = &stats.operators[1];
+    assert!(zero.abilities.is_none());
+    // The unknown CTU falls back to the metadata
+    assert_eq!(zero.ctu(), Some(Ctu::Ros));
+
+    let clash = &stats.operators[2];
+    let abilities = clash.abilities.as_ref().unwrap();
+    assert!(abilities[0].value.is_none());
+}
+
+#[test]
+fn test_operator_metadata() {
+    assert_eq!(Operator::from("Jäger"), Operator::Jager);
+    assert_eq!(Operator::from("JAGER"), Operator::Jager);
+    assert_eq!(Operator::from("nokk"), Operator::Nokk);
+    assert_eq!(Operator::from("Capitao"), Operator::Capitao);
+    assert_eq!(
+        Operator::from("Flores"),
+        Operator::Unknown("Flores".to_string())
+    );
+    assert_eq!(Operator::Nokk.to_string(), "Nøkk");
+
+    let zero = Operator::Zero.metadata().unwrap();
+    assert_eq!(zero.role, Role::Attacker);
+    assert_eq!(zero.ability, "Argus Launcher");
+    assert_eq!(zero.release_season, Some(Season::ShadowLegacy));
+
+    let ash = Operator::Ash.metadata().unwrap();
+    assert_eq!(ash.ctu, Ctu::FbiSwat);
+    assert!(ash.release_season.is_none());
+
+    assert!(Operator::Recruit.metadata().is_none());
+    assert!(Operator::from("Flores").metadata().is_none());
+
+    assert_eq!(Ctu::from_name("gsg9"), Some(Ctu::Gsg9));
+    assert_eq!(Role::from_name("Defender"), Some(Role::Defender));
+    assert_eq!(Role::from_name("spectator"), None);
+}
+
+#[test]
+fn test_weapon_categories() {
+    let stats: WeaponCategoryStats = fixture!("weapon_categories");
+
+    assert_eq!(stats.categories.len(), 2);
+    assert_eq!(stats.categories[0].category, "Assault Rifle");
+    assert_eq!(stats.categories[1].times_chosen, 2230);
+}
+
+#[test]
+fn test_weapons() {
+    let stats: WeaponStats = fixture!("weapons");
+
+    assert_eq!(stats.weapons.len(), 2);
+    assert_eq!(stats.weapons[0].weapon, "R4-C");
+    assert_eq!(stats.weapons[1].category, "Shotgun");
+}
+
+#[test]
+fn test_leaderboard() {
+    let leaderboard: Leaderboard = fixture!("leaderboard");
+
+    assert_eq!(leaderboard.players.len(), 2);
+    assert_eq!(leaderboard.players[0].position, 1);
+    assert_eq!(leaderboard.players[1].username, "pengu.g2");
+    assert_eq!(leaderboard.players[1].stats.level, 312);
+}
+
+#[test]
+fn test_leaderboard_null_avatars() {
+    let leaderboard: Leaderboard = fixture!("leaderboard_null_avatars");
+
+    let player = &leaderboard.players[0];
+    assert!(player.uplay_id.is_none());
+    assert!(player.avatar_url_146.is_none());
+    assert!(player.avatar_url_256.is_none());
+}
+
+#[test]
+fn test_lenient_clean() {
+    let stats =
+        Lenient::<GenericStats>::from_slice(include_bytes!("fixtures/generic.json")).unwrap();
+
+    assert!(stats.is_clean());
+    assert_eq!(stats.value.progression.level, 312);
+}
+
+#[test]
+fn test_lenient_generic() {
+    let mut value: Value = fixture!("generic");
+    value["progression"]["level"] = json!(70000);
+    value["stats"]["general"]["kills"] = json!("29311");
+    value["stats"]["general"]["kd"] = json!("1.25");
+    value["stats"]["general"]["assists"] = json!(812.0);
+
+    let bytes = serde_json::to_vec(&value).unwrap();
+    assert!(serde_json::from_slice::<GenericStats>(&bytes).is_err());
+
+    let stats = Lenient::<GenericStats>::from_slice(&bytes).unwrap();
+    let general = &stats.value.stats.general;
+    assert_eq!(stats.value.progression.level, u16::MAX);
+    assert_eq!(general.kills, 29311);
+    assert_eq!(general.kd, 1.25);
+    assert_eq!(general.assists, 812);
+
+    let paths: Vec<_> = stats.issues.iter().map(|i| i.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "progression.level",
+            "stats.general.assists",
+            "stats.general.kd",
+            "stats.general.kills",
+        ]
+    );
+}
+
+#[test]
+fn test_lenient_seasonal() {
+    let mut value: Value = fixture!("seasonal");
+    let emea = &mut value["seasons"]["shadow_legacy"]["regions"]["emea"][0];
+    emea["last_match_result"] = json!(9);
+    emea["last_match_skill_mean_change"] = json!(0.412);
+
+    let bytes = serde_json::to_vec(&value).unwrap();
+    let stats = Lenient::<SeasonalStats>::from_slice(&bytes).unwrap();
+
+    let emea = &stats.value.seasons[&Season::ShadowLegacy].regions[&Region::Emea][0];
+    assert!(emea.last_match_result.is_none());
+    assert_eq!(emea.last_match_skill_mean_change, Some(0.412));
+
+    let path = "seasons.shadow_legacy.regions.emea[0]";
+    assert_eq!(stats.issues.len(), 2);
+    assert_eq!(stats.issues[0].path, format!("{}.last_match_result", path));
+    assert!(stats.issues[0].message.ends_with("ignored the value"));
+    assert_eq!(
+        stats.issues[1].path,
+        format!("{}.last_match_skill_mean_change", path)
+    );
+}
+
+#[test]
+fn test_lenient_required_value() {
+    let mut value: Value = fixture!("seasonal");
+    value["seasons"]["shadow_legacy"]["regions"]["emea"][0]["rank"] = json!(99);
+
+    let bytes = serde_json::to_vec(&value).unwrap();
+    assert!(Lenient::<SeasonalStats>::from_slice(&bytes).is_err());
+}
+
+#[test]
+fn test_round_trip() {
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+        let json = serde_json::to_string(&value).unwrap();
+        let back: T = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(back, value);
+    }
+
+    round_trip::<GenericStats>(fixture!("generic"));
+    round_trip::<GenericStats>(fixture!("generic_null_avatars"));
+    round_trip::<SeasonalStats>(fixture!("seasonal"));
+    round_trip::<SeasonalStats>(fixture!("seasonal_unknown_season"));
+    round_trip::<OperatorStats>(fixture!("operators"));
+    round_trip::<OperatorStats>(fixture!("operators_missing_abilities"));
+    round_trip::<WeaponCategoryStats>(fixture!("weapon_categories"));
+    round_trip::<WeaponStats>(fixture!("weapons"));
+    round_trip::<Leaderboard>(fixture!("leaderboard"));
+    round_trip::<Leaderboard>(fixture!("leaderboard_null_avatars"));
+    round_trip::<SearchResults>(fixture!("search"));
+}
+
+#[test]
+fn test_serialize_api_format() {
+    let stats: SeasonalStats = fixture!("seasonal");
+    let value = serde_json::to_value(&stats).unwrap();
+
+    let emea = &value["seasons"]["shadow_legacy"]["regions"]["emea"][0];
+    assert_eq!(emea["season_id"], 19);
+    assert_eq!(emea["rank"], 22);
+    assert_eq!(emea["last_match_skill_mean_change"], "0.412");
+    assert_eq!(emea["last_match_result"], 1);
+    assert!(emea["champions_rank_position"].is_null());
+
+    let leaderboard: Leaderboard = fixture!("leaderboard");
+    let value = serde_json::to_value(&leaderboard).unwrap();
+    assert!(value.is_array());
+    assert_eq!(value[0]["position"], 1);
+}
+
+#[test]
+fn test_serialize_unknown_season() {
+    let stats: SeasonalStats = fixture!("seasonal_unknown_season");
+    let value = serde_json::to_value(&stats).unwrap();
+
+    // Written back as sent by the api
+    let unknown = &value["seasons"]["crimson_heist"];
+    assert_eq!(unknown["regions"]["ncsa"][0]["season_id"], 21);
+    assert_eq!(value["seasons"].as_object().unwrap().len(), 2);
+
+    let back: SeasonalStats = serde_json::from_value(value).unwrap();
+    assert_eq!(back, stats);
+    assert!(back.seasons.contains_key(&Season::Unknown(21)));
+
+    // Without regions, the id is kept in the key
+    let mut stats = stats;
+    let mut unknown = stats.seasons.remove(&Season::Unknown(21)).unwrap();
+    unknown.regions.clear();
+    stats.seasons.insert(Season::Unknown(22), unknown);
+
+    let value = serde_json::to_value(&stats).unwrap();
+    assert!(value["seasons"]["unknown_22"].is_object());
+    let back: SeasonalStats = serde_json::from_value(value).unwrap();
+    assert!(back.seasons.contains_key(&Season::Unknown(22)));
+
+    assert_eq!(Season::from_id(19), Season::ShadowLegacy);
+    assert_eq!(Season::Unknown(21).id(), 21);
+    assert_eq!(Season::Unknown(21).to_string(), "Unknown (21)");
+}
+
+#[test]
+fn test_equality() {
+    let a: OperatorStats = fixture!("operators");
+    let b: OperatorStats = fixture!("operators");
+    assert_eq!(a, b);
+
+    let mut value: Value = fixture!("operators");
+    value["operators"][1]["kd"] = json!(0.5);
+    let c: OperatorStats = serde_json::from_value(value).unwrap();
+    assert_eq!(a.operators[0], c.operators[0]);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn test_fingerprint() {
+    let a: SeasonalStats = fixture!("seasonal");
+    let b: SeasonalStats = fixture!("seasonal");
+    assert_eq!(Fingerprint::of(&a), Fingerprint::of(&b));
+
+    let mut c = b.clone();
+    c.seasons
+        .get_mut(&Season::ShadowLegacy)
+        .unwrap()
+        .regions
+        .get_mut(&Region::Emea)
+        .unwrap()[0]
+        .wins += 1;
+    assert_ne!(Fingerprint::of(&a), Fingerprint::of(&c));
+
+    let leaderboard: Leaderboard = fixture!("leaderboard");
+    assert_ne!(Fingerprint::of(&leaderboard), Fingerprint::of(&a));
+}
+
+#[test]
+fn test_nan() {
+    let a: SeasonalStats = fixture!("seasonal");
+    let mut b = a.clone();
+    let region = &mut b
+        .seasons
+        .get_mut(&Season::ShadowLegacy)
+        .unwrap()
+        .regions
+        .get_mut(&Region::Emea)
+        .unwrap()[0];
+    region.mmr = f32::NAN;
+    region.last_match_skill_mean_change = None;
+
+    // Floats are compared bitwise, so `NaN` equals itself
+    let mut c = b.clone();
+    assert_eq!(b, c);
+    assert_ne!(a, b);
+    assert_eq!(Fingerprint::of(&b), Fingerprint::of(&c));
+
+    // `NaN` isn't mistaken for a missing value
+    c.seasons
+        .get_mut(&Season::ShadowLegacy)
+        .unwrap()
+        .regions
+        .get_mut(&Region::Emea)
+        .unwrap()[0]
+        .last_match_skill_mean_change = Some(f64::NAN);
+    assert_ne!(b, c);
+    assert_ne!(Fingerprint::of(&b), Fingerprint::of(&c));
+}
@@ -0,0 +1,210 @@
+#![cfg(feature = "ratelimiting")]
+
+use r6stats_client::http::{
+    BoxFuture, Priority, Ratelimit, RatelimitBackend, RatelimitHeaders, SharedRatelimit,
+};
+use r6stats_client::test_util::{fixtures, MockResponse, MockServer};
+use r6stats_client::{Error, Platform};
+use std::io;
+use tokio::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_ratelimit() {
+    let interval = Duration::from_millis(500);
+
+    let server = MockServer::start().await;
+    server.mount_leaderboard(Platform::Pc, None);
+
+    let client = server
+        .client_builder("TOKEN")
+        .ratelimit(|r| r.limit(2).interval(interval))
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+
+    for _ in 0..2 {
+        client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    }
+    assert_eq!(client.ratelimit().await.unwrap().remaining(), 0);
+
+    // Delayed until the next interval
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+    assert!(start.elapsed() >= interval);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_shared_ratelimit() {
+    let interval = Duration::from_millis(500);
+
+    let server = MockServer::start().await;
+    server.mount_leaderboard(Platform::Pc, None);
+
+    let ratelimit = SharedRatelimit::new(|r| r.limit(2).interval(interval));
+    let clients = (0..2)
+        .map(|_| {
+            server
+                .client_builder("TOKEN")
+                .ratelimit_backend(ratelimit.clone())
+                .build()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+
+    for client in clients.iter() {
+        client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    }
+    assert_eq!(clients[0].ratelimit().await.unwrap().remaining(), 0);
+
+    // The quota is used up by both clients together
+    clients[1]
+        .leaderboard()
+        .get(Platform::Pc, None)
+        .await
+        .unwrap();
+
+    assert!(start.elapsed() >= interval);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_server_ratelimit_headers() {
+    let server = MockServer::start().await;
+    server.mount(
+        "/leaderboard/pc/all",
+        MockResponse::json(fixtures::LEADERBOARD)
+            .header("X-RateLimit-Limit", "30")
+            .header("X-RateLimit-Remaining", "7")
+            .header("X-RateLimit-Reset", "20"),
+    );
+
+    let client = server.client_builder("TOKEN").build().unwrap();
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+    let ratelimit = client.ratelimit().await.unwrap();
+    assert_eq!(ratelimit.limit(), 30);
+    assert_eq!(ratelimit.remaining(), 7);
+
+    let reset_in = ratelimit.reset_in().unwrap();
+    assert!(reset_in <= Duration::from_secs(20));
+    assert!(reset_in > Duration::from_secs(15));
+}
+
+#[tokio::test]
+async fn test_retry_after() {
+    tokio::time::pause();
+
+    let server = MockServer::start().await;
+    server.mount_sequence(
+        "/leaderboard/pc/all",
+        vec![
+            MockResponse::status(429).header("Retry-After", "1"),
+            MockResponse::json(fixtures::LEADERBOARD),
+        ],
+    );
+
+    // The paused clock would skip ahead to the timeout of idle connections
+    let client = server
+        .client_builder("TOKEN")
+        .pool_max_idle_per_host(0)
+        .retry(|r| r.max_attempts(2).initial_backoff(Duration::from_millis(1)))
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_secs(1));
+    // Waits for the `Retry-After`, not for the reset of the whole interval
+    assert!(elapsed < Duration::from_secs(2));
+    assert_eq!(server.requests().len(), 2);
+}
+
+/// A backend which can't record the ratelimit headers.
+#[derive(Debug)]
+struct FailingUpdate(SharedRatelimit);
+
+impl RatelimitBackend for FailingUpdate {
+    fn acquire(&self, priority: Priority) -> BoxFuture<'_, Result<(), Error>> {
+        self.0.acquire(priority)
+    }
+
+    fn update(&self, _headers: RatelimitHeaders) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async { Err(io::Error::other("disk full").into()) })
+    }
+
+    fn exhaust(&self) -> BoxFuture<'_, Result<bool, Error>> {
+        self.0.exhaust()
+    }
+
+    fn ratelimit(&self) -> BoxFuture<'_, Result<Ratelimit, Error>> {
+        self.0.ratelimit()
+    }
+}
+
+#[tokio::test]
+async fn test_backend_update_error_ignored() {
+    let server = MockServer::start().await;
+    server.mount(
+        "/leaderboard/pc/all",
+        MockResponse::json(fixtures::LEADERBOARD),
+    );
+
+    let client = server
+        .client_builder("TOKEN")
+        .ratelimit_backend(FailingUpdate(SharedRatelimit::new(|r| r)))
+        .build()
+        .unwrap();
+
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+}
+
+/// A backend which can't record a server-side 429.
+#[derive(Debug)]
+struct FailingExhaust(SharedRatelimit);
+
+impl RatelimitBackend for FailingExhaust {
+    fn acquire(&self, priority: Priority) -> BoxFuture<'_, Result<(), Error>> {
+        self.0.acquire(priority)
+    }
+
+    fn update(&self, headers: RatelimitHeaders) -> BoxFuture<'_, Result<(), Error>> {
+        self.0.update(headers)
+    }
+
+    fn exhaust(&self) -> BoxFuture<'_, Result<bool, Error>> {
+        Box::pin(async { Err(io::Error::other("disk full").into()) })
+    }
+
+    fn ratelimit(&self) -> BoxFuture<'_, Result<Ratelimit, Error>> {
+        self.0.ratelimit()
+    }
+}
+
+#[tokio::test]
+async fn test_backend_exhaust_error_ignored() {
+    let server = MockServer::start().await;
+    server.mount("/leaderboard/pc/all", MockResponse::status(429));
+
+    let client = server
+        .client_builder("TOKEN")
+        .retry(|r| r.max_attempts(3).initial_backoff(Duration::from_millis(1)))
+        .ratelimit_backend(FailingExhaust(SharedRatelimit::new(|r| r)))
+        .build()
+        .unwrap();
+
+    let err = client
+        .leaderboard()
+        .get(Platform::Pc, None)
+        .await
+        .unwrap_err();
+
+    // The 429 is returned instead of the error of the backend
+    assert!(matches!(err, Error::RateLimited { .. }));
+    assert_eq!(server.requests().len(), 1);
+}
@@ -0,0 +1,206 @@
+use r6stats_client::http::Jitter;
+use r6stats_client::test_util::{fixtures, MockResponse, MockServer};
+use r6stats_client::{Error, Platform};
+use reqwest::StatusCode;
+use tokio::time::Duration;
+#[cfg(feature = "ratelimiting")]
+use tokio::time::Instant;
+
+const PATH: &str = "/leaderboard/pc/all";
+
+#[tokio::test]
+async fn test_retry_transient_failures() {
+    let server = MockServer::start().await;
+    server.mount_sequence(
+        PATH,
+        vec![
+            MockResponse::status(502),
+            MockResponse::status(503),
+            MockResponse::json(fixtures::LEADERBOARD),
+        ],
+    );
+
+    let client = server
+        .client_builder("TOKEN")
+        .retry(|r| {
+            r.max_attempts(3)
+                .initial_backoff(Duration::from_millis(10))
+                .jitter(Jitter::None)
+        })
+        .build()
+        .unwrap();
+
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_retry_gives_up() {
+    let server = MockServer::start().await;
+    server.mount(PATH, MockResponse::status(500));
+
+    let client = server
+        .client_builder("TOKEN")
+        .retry(|r| r.max_attempts(2).initial_backoff(Duration::from_millis(10)))
+        .build()
+        .unwrap();
+
+    let err = client
+        .leaderboard()
+        .get(Platform::Pc, None)
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::ServerError {
+            status,
+            ref message,
+            ref source,
+        } => {
+            assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+            assert_eq!(*message, None);
+            assert_eq!(source.url(), Some(server.base_url() + &PATH[1..]));
+        }
+        ref err => panic!("Unexpected error: {:?}", err),
+    }
+    assert!(err.is_retryable());
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_no_retry_on_client_error() {
+    let server = MockServer::start().await;
+
+    let client = server
+        .client_builder("TOKEN")
+        .retry(|r| r.max_attempts(5).initial_backoff(Duration::from_millis(10)))
+        .build()
+        .unwrap();
+
+    let err = client
+        .leaderboard()
+        .get(Platform::Pc, None)
+        .await
+        .unwrap_err();
+
+    assert_status(err, StatusCode::NOT_FOUND);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_retry_disabled_by_default() {
+    let server = MockServer::start().await;
+    server.mount(PATH, MockResponse::status(503));
+
+    let client = server.client_builder("TOKEN").build().unwrap();
+
+    let _ = client.leaderboard().get(Platform::Pc, None).await;
+
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limited() {
+    let server = MockServer::start().await;
+    server.mount(
+        PATH,
+        MockResponse::status(429)
+            .header("Retry-After", "5")
+            .body(r#"{"status":"error","error":"Too Many Requests"}"#),
+    );
+
+    let client = server.client_builder("TOKEN").build().unwrap();
+
+    let err = client
+        .leaderboard()
+        .get(Platform::Pc, None)
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::RateLimited {
+            retry_after,
+            ref message,
+            ..
+        } => {
+            assert_eq!(retry_after, Some(Duration::from_secs(5)));
+            assert_eq!(message.as_deref(), Some("Too Many Requests"));
+        }
+        ref err => panic!("Unexpected error: {:?}", err),
+    }
+    assert!(err.is_retryable());
+    assert!(!err.is_client_error());
+}
+
+#[cfg(feature = "ratelimiting")]
+#[tokio::test]
+async fn test_retry_rate_limited_waits_for_reset() {
+    let interval = Duration::from_millis(300);
+
+    let server = MockServer::start().await;
+    server.mount_sequence(
+        PATH,
+        vec![
+            MockResponse::status(429),
+            MockResponse::json(fixtures::LEADERBOARD),
+        ],
+    );
+
+    let client = server
+        .client_builder("TOKEN")
+        .ratelimit(|r| r.limit(10).interval(interval))
+        .retry(|r| r.max_attempts(2).initial_backoff(Duration::from_millis(1)))
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+    // The local ratelimit was drained, so the retry waited for the next interval
+    assert!(start.elapsed() >= interval);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[cfg(feature = "ratelimiting")]
+#[tokio::test]
+async fn test_retry_after_capped_by_max_backoff() {
+    tokio::time::pause();
+
+    let server = MockServer::start().await;
+    server.mount_sequence(
+        PATH,
+        vec![
+            MockResponse::status(429).header("Retry-After", "3600"),
+            MockResponse::json(fixtures::LEADERBOARD),
+        ],
+    );
+
+    // The paused clock would skip ahead to the timeout of idle connections
+    let client = server
+        .client_builder("TOKEN")
+        .pool_max_idle_per_host(0)
+        .ratelimit(|r| r.limit(0))
+        .retry(|r| {
+            r.max_attempts(2)
+                .initial_backoff(Duration::from_millis(1))
+                .max_backoff(Duration::from_secs(1))
+        })
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    client.leaderboard().get(Platform::Pc, None).await.unwrap();
+
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_secs(1));
+    assert!(elapsed < Duration::from_secs(2));
+    assert_eq!(server.requests().len(), 2);
+}
+
+fn assert_status(err: Error, status: StatusCode) {
+    match err {
+        Error::HttpError(err) => assert_eq!(err.unsuccessful_request(), Some(status)),
+        err => panic!("Unexpected error: {:?}", err),
+    }
+}
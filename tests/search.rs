@@ -0,0 +1,66 @@
+use r6stats_client::test_util::{search_path, MockServer};
+use r6stats_client::{Error, Platform};
+
+const TOKEN: &str = "TOKEN";
+
+#[tokio::test]
+async fn test_search() {
+    let server = MockServer::start().await;
+    server.expect_token(TOKEN);
+
+    let searches = [("pengu", None), ("i jefe", Some(Platform::Xbox))];
+    for &(query, platform) in searches.iter() {
+        server.mount_search(query, platform);
+    }
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    for &(query, platform) in searches.iter() {
+        let results = client.search().players(query, platform).await.unwrap();
+        assert_eq!(results.players.len(), 2);
+    }
+
+    let paths = server
+        .requests()
+        .into_iter()
+        .map(|r| r.path)
+        .collect::<Vec<_>>();
+
+    assert_eq!(paths, ["/search/pengu/all", "/search/i%20jefe/xbox"]);
+}
+
+#[tokio::test]
+async fn test_search_candidates() {
+    let server = MockServer::start().await;
+    server.mount_search("pengu", Some(Platform::Pc));
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let results = client
+        .search()
+        .players("pengu", Some(Platform::Pc))
+        .await
+        .unwrap();
+
+    let best = &results.players[0];
+    assert_eq!(best.username, "pengu.g2");
+    assert_eq!(best.platform, "pc");
+    assert_eq!(best.ubisoft_id, "8a69b38e-3a5a-4a9a-a1bc-d1f4aa8dd1a1");
+    assert!(best.avatar_url_146.is_some());
+    assert!(results.players[1].avatar_url_256.is_none());
+
+    assert_eq!(
+        server.requests()[0].path,
+        search_path("pengu", Some(Platform::Pc))
+    );
+}
+
+#[tokio::test]
+async fn test_search_malformed_query() {
+    let server = MockServer::start().await;
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    let err = client.search().players("", None).await.unwrap_err();
+
+    assert!(matches!(err, Error::QueryMalformed));
+    assert!(server.requests().is_empty());
+}
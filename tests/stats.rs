@@ -1,10 +1,30 @@
-use r6stats_client::{Client, Platform};
-use std::env;
+use futures::StreamExt;
+use r6stats_client::stats::model::OperatorStats;
+use r6stats_client::stats::{Kind, PlayerId};
+use r6stats_client::test_util::{fixtures, stats_path, MockResponse, MockServer};
+use r6stats_client::{Error, Platform};
+use reqwest::StatusCode;
+use std::time::{Duration, Instant};
+
+const TOKEN: &str = "TOKEN";
 
 #[tokio::test]
 async fn test_stats() {
-    let token = env::var("R6STATS_TOKEN").expect("API key not found in env.");
-    let client = Client::new(&token).expect("Error creating client.");
+    let server = MockServer::start().await;
+    server.expect_token(TOKEN);
+
+    let players = [
+        ("pengu.g2", Platform::Pc, Kind::Generic),
+        ("i jefe l", Platform::Xbox, Kind::Seasonal),
+        ("WhyGunner", Platform::Playstation, Kind::Operators),
+        ("pengu.g2", Platform::Pc, Kind::WeaponCategories),
+        ("pengu.g2", Platform::Pc, Kind::Weapons),
+    ];
+    for &(username, platform, kind) in players.iter() {
+        server.mount_stats(username, platform, kind);
+    }
+
+    let client = server.client_builder(TOKEN).build().unwrap();
 
     let _ = client
         .stats()
@@ -31,4 +51,376 @@ async fn test_stats() {
         .weapons("pengu.g2", Platform::Pc)
         .await
         .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), players.len());
+
+    for (request, &(username, platform, kind)) in requests.iter().zip(players.iter()) {
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, stats_path(username, platform, kind));
+        assert_eq!(request.token.as_deref(), Some(TOKEN));
+    }
+
+    assert_eq!(requests[1].path, "/stats/i%20jefe%20l/xbox/seasonal");
+}
+
+#[tokio::test]
+async fn test_stats_not_found() {
+    let server = MockServer::start().await;
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    let err = client
+        .stats()
+        .generic("unknown", Platform::Pc)
+        .await
+        .unwrap_err();
+
+    assert_not_found(&err, "unknown");
+    assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+    assert!(err.is_client_error());
+    assert!(!err.is_retryable());
+}
+
+#[tokio::test]
+async fn test_stats_not_found_message() {
+    let server = MockServer::start().await;
+    server.mount(
+        stats_path("pengu.g2", Platform::Pc, Kind::Generic),
+        MockResponse::status(404).body(r#"{"status":"error","error":"Player has no stats"}"#),
+    );
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    let err = client
+        .stats()
+        .generic("pengu.g2", Platform::Pc)
+        .await
+        .unwrap_err();
+
+    match &err {
+        Error::PlayerNotFound { message, .. } => {
+            assert_eq!(message.as_deref(), Some("Player has no stats"))
+        }
+        err => panic!("Unexpected error: {:?}", err),
+    }
+    assert_eq!(
+        err.to_string(),
+        "Player pengu.g2 not found on pc (Player has no stats)."
+    );
+
+    // The response is kept along with the typed error
+    let response = err.http_error().unwrap();
+    assert_eq!(response.unsuccessful_request(), Some(StatusCode::NOT_FOUND));
+    assert!(response
+        .url()
+        .unwrap()
+        .ends_with("/stats/pengu.g2/pc/generic"));
+    assert!(response.body().is_some());
+    assert_eq!(response.api_error().unwrap().message, "Player has no stats");
+}
+
+#[tokio::test]
+async fn test_stats_unauthorized() {
+    let server = MockServer::start().await;
+    server.expect_token(TOKEN);
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Generic);
+
+    let client = server.client_builder("WRONG").build().unwrap();
+
+    let err = client
+        .stats()
+        .generic("pengu.g2", Platform::Pc)
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::Unauthorized { ref message, .. } => {
+            assert_eq!(message.as_deref(), Some("Unauthorized"))
+        }
+        ref err => panic!("Unexpected error: {:?}", err),
+    }
+    assert_eq!(
+        err.to_string(),
+        "Unauthorized, the token is invalid (Unauthorized)."
+    );
+    assert_eq!(
+        err.http_error().unwrap().body(),
+        Some(&br#"{"status":"error","error":"Unauthorized"}"#[..])
+    );
+    assert!(err.is_client_error());
+    assert!(!err.is_retryable());
+}
+
+#[tokio::test]
+async fn test_stats_username_malformed() {
+    let server = MockServer::start().await;
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    let err = client
+        .stats()
+        .generic("pengu/g2", Platform::Pc)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::UsernameMalformed));
+    assert!(server.requests().is_empty());
+}
+
+fn assert_not_found(err: &Error, expected: &str) {
+    match err {
+        Error::PlayerNotFound {
+            username, platform, ..
+        } => {
+            assert_eq!(username, expected);
+            assert_eq!(platform.as_str(), "pc");
+        }
+        err => panic!("Unexpected error: {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn test_all() {
+    let server = MockServer::start().await;
+    let kinds = [
+        Kind::Generic,
+        Kind::Seasonal,
+        Kind::Operators,
+        Kind::WeaponCategories,
+        Kind::Weapons,
+    ];
+    for &kind in kinds.iter() {
+        server.mount_stats("pengu.g2", Platform::Pc, kind);
+    }
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let profile = client.stats().all("pengu.g2", Platform::Pc).await.unwrap();
+
+    assert!(profile.is_complete());
+    assert_eq!(profile.username, "pengu.g2");
+    assert!(profile.stats.is_some());
+    assert!(profile.seasons.is_some());
+    assert!(profile.operators.is_some());
+    assert!(profile.weapon_categories.is_some());
+    assert!(profile.weapons.is_some());
+    assert_eq!(server.requests().len(), 5);
+}
+
+#[tokio::test]
+async fn test_all_partial() {
+    let server = MockServer::start().await;
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Seasonal);
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Weapons);
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let profile = client.stats().all("pengu.g2", Platform::Pc).await.unwrap();
+
+    assert!(!profile.is_complete());
+    assert_eq!(profile.username, "pengu.g2");
+    assert!(profile.stats.is_none());
+    assert!(profile.seasons.is_some());
+    assert!(profile.operators.is_none());
+    assert!(profile.weapons.is_some());
+
+    let mut failed: Vec<_> = profile.errors.keys().map(|k| k.as_str()).collect();
+    failed.sort_unstable();
+    assert_eq!(failed, ["generic", "operators", "weapon-categories"]);
+
+    match &*profile.errors[&Kind::Generic] {
+        Error::PlayerNotFound { username, .. } => assert_eq!(username, "pengu.g2"),
+        err => panic!("Unexpected error: {:?}", err),
+    }
+    assert_eq!(profile.clone(), profile);
+
+    let json = serde_json::to_value(&profile).unwrap();
+    assert_eq!(json["username"], "pengu.g2");
+    assert!(json.get("errors").is_none());
+}
+
+#[tokio::test]
+async fn test_all_failed() {
+    let server = MockServer::start().await;
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let err = client
+        .stats()
+        .all("pengu.g2", Platform::Pc)
+        .await
+        .unwrap_err();
+
+    assert_not_found(&err, "pengu.g2");
+}
+
+#[tokio::test]
+async fn test_lenient() {
+    let server = MockServer::start().await;
+    server.mount(
+        stats_path("pengu.g2", Platform::Pc, Kind::Operators),
+        MockResponse::json(fixtures::OPERATORS.replace(r#""wins": 612"#, r#""wins": "612""#)),
+    );
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+
+    let err = client
+        .stats()
+        .operators("pengu.g2", Platform::Pc)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::JsonError(_)));
+
+    let stats = client
+        .stats()
+        .lenient::<OperatorStats>("pengu.g2", Platform::Pc)
+        .await
+        .unwrap();
+
+    assert_eq!(stats.value.operators[0].wins, 612);
+    assert_eq!(stats.issues.len(), 1);
+    assert_eq!(stats.issues[0].path, "operators[0].wins");
+    assert_eq!(
+        server.requests()[1].path,
+        stats_path("pengu.g2", Platform::Pc, Kind::Operators)
+    );
+}
+
+#[tokio::test]
+async fn test_generic_many() {
+    let server = MockServer::start().await;
+    let delay = Duration::from_millis(50);
+    let players = ["a", "b", "c", "d"];
+    for &username in players.iter() {
+        server.mount(
+            stats_path(username, Platform::Pc, Kind::Generic),
+            MockResponse::json(fixtures::GENERIC).delay(delay),
+        );
+    }
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let mut batch = client.stats().with_max_in_flight(2).generic_many(
+        players
+            .iter()
+            .chain(&["unknown"])
+            .map(|&username| (username, Platform::Pc)),
+    );
+    assert_eq!(batch.total(), 5);
+
+    let start = Instant::now();
+    let mut failed = Vec::new();
+    while let Some(player) = batch.next().await {
+        if player.result.is_err() {
+            failed.push(player.player);
+        }
+    }
+
+    // Two rounds of two delayed requests each
+    assert!(start.elapsed() >= delay * 2);
+    assert_eq!(batch.completed(), 5);
+    assert_eq!(batch.remaining(), 0);
+    assert_eq!(failed, [PlayerId::from("unknown")]);
+    assert_eq!(server.requests().len(), 5);
+}
+
+#[tokio::test]
+async fn test_many_cancelled() {
+    let server = MockServer::start().await;
+    let players = ["a", "b", "c", "d"];
+    for &username in players.iter() {
+        server.mount_stats(username, Platform::Pc, Kind::Weapons);
+    }
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let mut batch = client
+        .stats()
+        .with_max_in_flight(1)
+        .weapons_many(players.iter().map(|&username| (username, Platform::Pc)));
+
+    let first = batch.next().await.unwrap();
+    assert_eq!(first.player, PlayerId::from("a"));
+    assert!(first.result.is_ok());
+    drop(batch);
+
+    // At most the next lookup was started before the stream was dropped
+    assert!(server.requests().len() <= 2);
+}
+
+#[tokio::test]
+async fn test_ubisoft_id() {
+    let server = MockServer::start().await;
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Generic);
+    server.mount_stats("pengu.g2", Platform::Pc, Kind::Seasonal);
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let unknown = PlayerId::UbisoftId("unknown".to_string());
+    let err = client
+        .stats()
+        .generic(unknown, Platform::Pc)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownUbisoftId(_)));
+    assert!(server.requests().is_empty());
+
+    // The Ubisoft ID is learned from the response
+    let generic = client
+        .stats()
+        .generic("pengu.g2", Platform::Pc)
+        .await
+        .unwrap();
+    let id = PlayerId::UbisoftId(generic.ubisoft_id.clone());
+    assert_eq!(
+        client.stats().resolver().username(&generic.ubisoft_id),
+        Some("pengu.g2".to_string())
+    );
+
+    let seasonal = client.stats().seasonal(id, Platform::Pc).await.unwrap();
+    assert_eq!(seasonal.username, "pengu.g2");
+    assert_eq!(
+        server.requests()[1].path,
+        stats_path("pengu.g2", Platform::Pc, Kind::Seasonal)
+    );
+}
+
+#[tokio::test]
+async fn test_ubisoft_id_from_other_endpoints() {
+    let server = MockServer::start().await;
+    server.mount_leaderboard(Platform::Pc, None);
+    server.mount_search("pengu", None);
+
+    let client = server.client_builder(TOKEN).build().unwrap();
+    let resolver = client.stats().resolver();
+
+    let leaderboard = client.leaderboard().get(Platform::Pc, None).await.unwrap();
+    let player = &leaderboard.players[0];
+    assert_eq!(
+        resolver.username(&player.ubisoft_id),
+        Some(player.username.clone())
+    );
+
+    let results = client.search().players("pengu", None).await.unwrap();
+    let candidate = &results.players[0];
+    assert_eq!(
+        resolver.username(&candidate.ubisoft_id),
+        Some(candidate.username.clone())
+    );
+}
+
+#[tokio::test]
+async fn test_resolver_capacity() {
+    let server = MockServer::start().await;
+    let client = server
+        .client_builder(TOKEN)
+        .resolver_capacity(2)
+        .build()
+        .unwrap();
+    let resolver = client.stats().resolver();
+    assert_eq!(resolver.capacity(), 2);
+
+    resolver.insert("a", "alpha");
+    resolver.insert("b", "bravo");
+    // Using "a" makes "b" the least recently used
+    assert!(resolver.username("a").is_some());
+    resolver.insert("c", "charlie");
+
+    assert_eq!(resolver.len(), 2);
+    assert_eq!(resolver.username("a"), Some("alpha".to_string()));
+    assert_eq!(resolver.username("b"), None);
+    assert_eq!(resolver.username("c"), Some("charlie".to_string()));
 }